@@ -0,0 +1,45 @@
+// draws a full screen quad squeezed horizontally by u_angle
+#shader vertex
+const vec2 verts[6] = vec2[6](
+    vec2(-1.0, 1.0),
+    vec2(-1.0, -1.0),
+    vec2(1.0, -1.0),
+    vec2(1.0, -1.0),
+    vec2(1.0, 1.0),
+    vec2(-1.0, 1.0)
+);
+const vec4 colors[6] = vec4[6](
+    vec4(1.0, 1.0, 1.0, 1.0),
+    vec4(1.0, 1.0, 1.0, 1.0),
+    vec4(1.0, 1.0, 1.0, 1.0),
+    vec4(1.0, 1.0, 1.0, 1.0),
+    vec4(1.0, 1.0, 1.0, 1.0),
+    vec4(1.0, 1.0, 1.0, 1.0)
+);
+const vec2 tex[6] = vec2[6](
+    vec2(0.0, 0.0),
+    vec2(0.0, 1.0),
+    vec2(1.0, 1.0),
+    vec2(1.0, 1.0),
+    vec2(1.0, 0.0),
+    vec2(0.0, 0.0)
+);
+out vec4 v_color;
+out vec2 TexCoord;
+uniform float u_angle;
+void main() {
+    v_color = colors[gl_VertexID];
+    gl_Position = vec4(verts[gl_VertexID], 0.0, 1.0);
+    gl_Position.x *= cos(u_angle);
+    TexCoord = tex[gl_VertexID];
+}
+
+#shader fragment
+precision mediump float;
+in vec4 v_color;
+in vec2 TexCoord;
+out vec4 out_color;
+uniform sampler2D ourTexture;
+void main() {
+    out_color = v_color * texture(ourTexture, TexCoord);
+}
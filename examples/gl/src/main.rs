@@ -2,24 +2,30 @@
 use ggsdk::egui::{Align2, Color32, FontId, Id, LayerId, Pos2};
 use ggsdk::{egui, glow, InitContext, UpdateContext};
 use ggsdk::glow::HasContext as _;
-use ggsdk::GGAtlas;
+use ggsdk::{GGAtlas, GGShader, GGSprite, Handle, SpriteBatch};
 
 
 struct State {
     pub vertex_array: glow::VertexArray,
-    pub program: glow::Program,
     pub angle:f32
 }
 
 struct App {
     state:Option<State>,
+    smilie:Option<Handle<GGAtlas>>,
+    quad:Option<Handle<GGShader>>,
+    sprites:Option<SpriteBatch>,
     iterations:u64
 }
+#[allow(clippy::derivable_impls)]
 impl Default for App {
     fn default() -> Self {
         Self {
             iterations:0,
-            state:None
+            state:None,
+            smilie:None,
+            quad:None,
+            sprites:None
         }
     }
 }
@@ -27,92 +33,17 @@ impl Default for App {
 impl ggsdk::GGApp for App {
     fn init(&mut self, g: InitContext) {
 
-        g.assets.load::<GGAtlas>("smilie_1x1.png", "smilie");
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
-        let (vertex_shader_source, fragment_shader_source) = (
-            r#"
-                const vec2 verts[6] = vec2[6](
-                    vec2(-1.0, 1.0),
-                    vec2(-1.0, -1.0),
-                    vec2(1.0, -1.0),
-                    vec2(1.0, -1.0),
-                    vec2(1.0, 1.0),
-                    vec2(-1.0, 1.0)
-                );
-                const vec4 colors[6] = vec4[6](
-                    vec4(1.0, 1.0, 1.0, 1.0),
-                    vec4(1.0, 1.0, 1.0, 1.0),
-                    vec4(1.0, 1.0, 1.0, 1.0),
-                    vec4(1.0, 1.0, 1.0, 1.0),
-                    vec4(1.0, 1.0, 1.0, 1.0),
-                    vec4(1.0, 1.0, 1.0, 1.0)
-                );
-                const vec2 tex[6] = vec2[6](
-                    vec2(0.0, 0.0),
-                    vec2(0.0, 1.0),
-                    vec2(1.0, 1.0),
-                    vec2(1.0, 1.0),
-                    vec2(1.0, 0.0),
-                    vec2(0.0, 0.0)
-                );
-                out vec4 v_color;
-                out vec2 TexCoord;
-                uniform float u_angle;
-                void main() {
-                    v_color = colors[gl_VertexID];
-                    gl_Position = vec4(verts[gl_VertexID], 0.0, 1.0);
-                    gl_Position.x *= cos(u_angle);
-                    TexCoord = tex[gl_VertexID];
-                }
-            "#,
-            r#"
-                precision mediump float;
-                in vec4 v_color;
-                in vec2 TexCoord;
-                out vec4 out_color;
-                uniform sampler2D ourTexture;
-                void main() {
-                    out_color = v_color *  texture(ourTexture, TexCoord);
-                }
-            "#,
-        );
-
-        let shader_sources = [
-            (glow::VERTEX_SHADER, vertex_shader_source),
-            (glow::FRAGMENT_SHADER, fragment_shader_source),
-        ];
+        self.smilie = Some(g.assets.load::<GGAtlas>("smilie_1x1.png", "smilie"));
+        self.quad = Some(g.assets.load::<GGShader>("quad.glsl", "quad"));
 
         unsafe {
-            let gl = g.gl;
-            let program = gl.create_program().expect("Cannot create program");
-            let _: Vec<_> = shader_sources
-                .iter()
-                .map(|(shader_type, shader_source)| {
-                    let shader = gl
-                        .create_shader(*shader_type)
-                        .expect("Cannot create shader");
-                    gl.shader_source(shader, &format!("{shader_version}\n{shader_source}"));
-                    gl.compile_shader(shader);
-                    assert!(
-                        gl.get_shader_compile_status(shader),
-                        "Failed to compile {shader_type}: {}",
-                        gl.get_shader_info_log(shader)
-                    );
-                    gl.attach_shader(program, shader);
-                    shader
-                })
-                .collect();
-            gl.link_program(program);
+            let gl = g.gl.expect("gl example requires a gl context");
+            self.sprites = Some(SpriteBatch::new(gl).expect("failed to create sprite batch"));
 
             let vertex_array = gl.create_vertex_array().expect("failed to create");
 
             self.state = Some(State {
                 vertex_array,
-                program,
                 angle:0.0
             });
         }
@@ -120,7 +51,7 @@ impl ggsdk::GGApp for App {
 
     fn update(&mut self, g: UpdateContext) {
         self.iterations += 1;
-        if g.assets.pending() != 0 {
+        if !self.smilie.as_ref().is_some_and(Handle::is_loaded) {
             return;
         }
        
@@ -143,26 +74,38 @@ impl ggsdk::GGApp for App {
     }
 
     fn paint_glow(&mut self, g:ggsdk::PaintGlowContext) {
-        if g.assets.pending() != 0 {
+        let Some(smilie) = self.smilie.as_ref().and_then(Handle::get) else {
             return;
-        }
-        let smilie_atlas = g.assets.get::<GGAtlas>("smilie").unwrap().texture_id();
+        };
+        let Some(quad) = self.quad.as_ref().and_then(Handle::get) else {
+            return;
+        };
+        let smilie_atlas = smilie.texture_id();
         let painter = g.painter;
         let state = self.state.as_mut().unwrap();
         let gl = g.painter.gl();
         unsafe { 
             let texture = painter.texture(smilie_atlas).unwrap();
             gl.enable(glow::FRAMEBUFFER_SRGB);
-            gl.use_program(Some(state.program.clone())); 
+            quad.bind();
             gl.bind_texture(glow::TEXTURE_2D, Some(texture));
-            gl.uniform_1_f32(
-                gl.get_uniform_location(state.program, "u_angle").as_ref(),
-                state.angle,
-            );
+            quad.set_f32("u_angle", state.angle);
             gl.bind_vertex_array(Some(state.vertex_array));
             gl.draw_arrays(glow::TRIANGLES, 0, 6);
             gl.disable(glow::FRAMEBUFFER_SRGB);
         };
+
+        // a few thousand small smilies in a single draw call
+        let sprites = self.sprites.as_mut().unwrap();
+        sprites.clear();
+        for i in 0..4096 {
+            let (x, y) = ((i % 64) as f32, (i / 64) as f32);
+            let sprite = GGSprite::at(Pos2::new(16.0 + x * 12.0, 64.0 + y * 12.0))
+                .with_size(egui::vec2(10.0, 10.0))
+                .with_rotation(state.angle + (x + y) * 0.1);
+            sprites.push_sprite(&smilie, 0, &sprite);
+        }
+        sprites.draw(painter, g.info);
     }
 }
 
@@ -4,6 +4,7 @@ pub struct CollectTreasureAction {
 }
 
 impl Action for CollectTreasureAction {
+    #[allow(clippy::collapsible_if)]
     fn exec(self:Box<Self>, ctx:&mut ActionContext)  {
         let mut treasure_left = 0;
         for chunk in &mut ctx.state.grid {
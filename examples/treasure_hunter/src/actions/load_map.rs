@@ -1,6 +1,7 @@
 use endlessgrid::Grid;
-use ggsdk::tiled;
-use crate::{Action, Cell, Thing, ThingVariant};
+use ggsdk::{tiled, PropertiesError};
+use serde::Deserialize;
+use crate::{Action, Cell, Thing};
 use endlessgrid;
 
 use super::FadeAction;
@@ -9,84 +10,86 @@ pub struct LoadMapAction {
     pub map_name: String,
 }
 
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct MapProperties {
+    next_level: String,
+    intro: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LayerClass {
+    Floor,
+    Walls,
+    Things,
+}
+
+fn load_grid(map: &tiled::Map) -> Result<(Grid<Cell>, MapProperties), PropertiesError> {
+    let properties: MapProperties = ggsdk::from_properties(&map.properties)?;
+    let mut grid: Grid<Cell> = Grid::default();
+    for layer in map.layers() {
+        let class: LayerClass = ggsdk::layer_class(&layer)?;
+        let Some(layer) = layer.as_tile_layer() else {
+            continue;
+        };
+        let w = layer.width().unwrap_or_default() as i32;
+        let h = layer.height().unwrap_or_default() as i32;
+        for y in 0..h {
+            for x in 0..w {
+                let Some(tile) = layer.get_tile(x, y) else {
+                    continue;
+                };
+
+                if grid.get((x, y)).is_none() {
+                    grid.insert((x, y), Default::default());
+                }
+
+                let cell = grid.get_mut((x, y)).unwrap();
+
+                match class {
+                    LayerClass::Floor => {
+                        cell.floor = tile.id() as u16;
+                    }
+                    LayerClass::Walls => {
+                        cell.walls.push(tile.id() as u16);
+                    }
+                    LayerClass::Things => {
+                        let atlas_index = tile.id() as u16;
+                        let Some(data) = tile.get_tile() else {
+                            return Err(PropertiesError::no_class().with_context(format!("tile {}", tile.id())));
+                        };
+                        let variant = ggsdk::tile_class(&data)?;
+                        cell.thing = Some(Thing {
+                            variant,
+                            atlas_index,
+                        });
+                    }
+                };
+            }
+        }
+    }
+    Ok((grid, properties))
+}
+
 impl Action for LoadMapAction {
+    #[allow(clippy::len_zero)]
     fn exec(self:Box<Self>, ctx: &mut crate::ActionContext) {
         let Some(map) = ctx.assets.get::<tiled::Map>(&self.map_name) else {
             return;
         };
 
-        let mut grid: Grid<Cell> = Grid::default();
         let map = &map.data;
-        let next_level: String = map
-            .properties
-            .get("next_level")
-            .and_then(|x| match x {
-                tiled::PropertyValue::StringValue(v) => Some(v.clone()),
-                _ => None,
-            })
-            .unwrap_or_default();
-        let intro: String = map
-            .properties
-            .get("intro")
-            .and_then(|x| match x {
-                tiled::PropertyValue::StringValue(v) => Some(v.clone()),
-                _ => None,
-            })
-            .unwrap_or_default();
-        
-        ctx.state.next_level = next_level;
-        for layer in map.layers() {
-            let class = layer.user_type.clone().unwrap_or_default();
-            let Some(layer) = layer.as_tile_layer() else {
-                continue;
-            };
-            let w = layer.width().unwrap() as i32;
-            let h = layer.height().unwrap() as i32;
-            for y in 0..h {
-                for x in 0..w {
-                    let Some(tile) = layer.get_tile(x, y) else {
-                        continue;
-                    };
-
-                    if grid.get((x, y)).is_none() {
-                        grid.insert((x, y), Default::default());
-                    }
-
-                    let cell = grid.get_mut((x, y)).unwrap();
-
-                    match class.as_str() {
-                        "floor" => {
-                            cell.floor = tile.id() as u16;
-                        }
-                        "walls" => {
-                            cell.walls.push(tile.id() as u16);
-                        }
-                        "things" => {
-                            let atlas_index = tile.id() as u16;
-                            let tile = tile.get_tile().unwrap();
-                            let class = tile.user_type.clone().unwrap_or_default();
-                            let variant = match class.as_str() {
-                                "player" => ThingVariant::Player {},
-                                "treasure" => ThingVariant::Treasure {},
-                                "door" => ThingVariant::Door {},
-                                "grate" => ThingVariant::Grate {},
-                                "exit" => ThingVariant::Exit {},
-                                "pit" => ThingVariant::Pit {},
-                                "monster" => ThingVariant::Monster { think: 0 },
-                                _ => panic!("unknown tile class: {class}"),
-                            };
-                            let thing = Thing {
-                                variant,
-                                atlas_index,
-                            };
-                            cell.thing = Some(thing);
-                        }
-                        _ => panic!("unknown layer class: {class}"),
-                    };
-                }
+        let (grid, properties) = match load_grid(map) {
+            Ok(level) => level,
+            Err(err) => {
+                ggsdk::tracing::error!("failed to load map {}: {err}", self.map_name);
+                return;
             }
-        }
+        };
 
+        ctx.state.next_level = properties.next_level;
+        let intro = properties.intro;
         ctx.state.won = false;
         ctx.state.grid = grid;
         ctx.state.show_menu = false;
@@ -5,6 +5,7 @@ use super::LostAction;
 pub struct MoveMonstersAction {}
 
 impl Action for MoveMonstersAction {
+    #[allow(clippy::needless_late_init)]
     fn exec(self:Box<Self>, ctx: &mut crate::ActionContext) {
         let Some(player_index) = ctx.state.find_player() else {
             return;
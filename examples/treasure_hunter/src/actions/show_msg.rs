@@ -6,6 +6,7 @@ pub struct ShowMessageAction {
 }
 
 impl Action for ShowMessageAction {
+    #[allow(clippy::clone_on_copy)]
     fn exec(mut self:Box<Self>, ctx:&mut crate::ActionContext) {
         ctx.state.msg = self.msg.clone();
         self.length_sec -= ctx.dt;
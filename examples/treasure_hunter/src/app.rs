@@ -1,5 +1,5 @@
 use ggsdk::{
-    egui::{self, Align2, Button, Color32, CornerRadius, FontId, LayerId, Margin, Rect, RichText}, kira, tiled, GGApp, GGAtlas, GGPainter, UpdateContext
+    egui::{self, pos2, vec2, Align2, Button, Color32, CornerRadius, FontId, LayerId, Margin, Rect, RichText}, kira, tiled, GGApp, GGAtlas, GGCamera2D, GGPainter, UpdateContext
 };
 use kira::sound::static_sound::StaticSoundData;
 use std::{cell::RefCell, rc::Rc};
@@ -15,6 +15,7 @@ pub struct TreasureHunter {
     pub font: FontId,
     pub font2: FontId,
     pub state: Rc<RefCell<State>>,
+    pub camera: GGCamera2D,
 }
 
 impl TreasureHunter {
@@ -24,6 +25,7 @@ impl TreasureHunter {
         }
     }
 
+    #[allow(clippy::bool_comparison)]
     pub fn process_game_input(&mut self, g: &mut UpdateContext) {
         let ctx = g.egui_ctx;
         ctx.input(|input| {
@@ -145,10 +147,23 @@ impl TreasureHunter {
             let Some(sound) = g.assets.get::<StaticSoundData>(&sound) else {
                 continue;
             };
-            let _ = g.audio_manager.play(sound.data.clone());
+            if let Some(audio_manager) = &mut g.audio_manager {
+                let _ = audio_manager.play(sound.data.clone());
+            }
         }
     }
 
+    fn update_camera(&mut self, g: &mut UpdateContext) {
+        // one world unit per tile
+        self.camera.viewport = g.egui_ctx.screen_rect();
+        self.camera.zoom = 32.0;
+        self.camera.update(g.dt);
+        if let Some((x, y)) = self.state.borrow().find_player() {
+            self.camera.follow(pos2(x as f32 + 0.5, y as f32 + 0.5), 8.0, g.dt);
+        }
+    }
+
+    #[allow(clippy::len_zero)]
     fn draw_game(&self, g: &mut UpdateContext) {
         let painter = g.egui_ctx.layer_painter(LayerId::background());
         let rect = painter.clip_rect();
@@ -184,13 +199,9 @@ impl TreasureHunter {
         let atlas = &atlas.data;
         let state = self.state.borrow();
 
-        fn calc_rect(x: i32, y: i32) -> Rect {
-            let size = 32.0;
-            Rect::from_min_size(
-                (x as f32 * size, y as f32 * size).into(),
-                (size, size).into(),
-            )
-        }
+        let calc_rect = |x: i32, y: i32| {
+            self.camera.transform_rect(Rect::from_min_size(pos2(x as f32, y as f32), vec2(1.0, 1.0)))
+        };
 
 
         // draw floor
@@ -250,10 +261,11 @@ impl TreasureHunter {
       
     }
 
+    #[allow(clippy::len_zero)]
     pub fn update_ui(&mut self, g: &mut UpdateContext) {
         let show_menu = self.state.borrow().show_menu;
         self.process_game_input(g);
-        self.process_actions(g);
+        self.update_camera(g);
         self.draw_game(g);
         self.play_sounds(g);
 
@@ -329,8 +341,15 @@ impl GGApp for TreasureHunter {
         state.current_level = ggsdk::persist::load::<String>("current_level").unwrap_or_default();
     }
 
+    fn fixed_update(&mut self, mut g: ggsdk::UpdateContext) {
+        self.process_actions(&mut g);
+    }
+
     fn update(&mut self, mut g: ggsdk::UpdateContext) {
         self.initialize(&mut g); 
+        if g.fixed_dt.is_none() {
+            self.process_actions(&mut g);
+        }
         self.update_ui(&mut g);
     }
 }
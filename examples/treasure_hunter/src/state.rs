@@ -1,11 +1,15 @@
 use std::collections::VecDeque;
 
 use endlessgrid::Grid;
+#[allow(clippy::single_component_path_imports)]
 use endlessgrid;
+use serde::Deserialize;
 
 use crate::Action;
 
-#[derive(Clone)]
+/// Matched to the class of the tiles of the things layer.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ThingVariant {
     Player {},
     Treasure {},
@@ -14,6 +18,7 @@ pub enum ThingVariant {
     Exit {},
     Pit {},
     Monster {
+        #[serde(default)]
         think:u32
     },
 }
@@ -48,6 +53,7 @@ pub struct State {
 }
 
 impl State {
+    #[allow(clippy::collapsible_if)]
     pub fn find_player(&self) -> Option<(i32, i32)> {
         for chunk in &self.grid {
             for (index, cell) in chunk {
@@ -62,6 +68,7 @@ impl State {
         None
     }
 
+    #[allow(clippy::collapsible_if)]
     pub fn find_monsters(&self) -> Vec<(i32, i32)> {
         let mut res = Vec::new();
         for chunk in &self.grid {
@@ -77,6 +84,7 @@ impl State {
         res
     }
 
+    #[allow(clippy::len_zero, clippy::match_like_matches_macro, clippy::needless_return)]
     pub fn path_find(&self, start: (i32, i32), end: (i32, i32)) -> VecDeque<(i32, i32)> {
         let mut path = self
             .grid
@@ -108,6 +116,7 @@ impl State {
         res
     }
 
+    #[allow(clippy::len_zero, clippy::bool_comparison, clippy::needless_ifs)]
     pub fn input_allowed(&self) -> bool {
         if self.fade != 0.0 {
             return false;
@@ -0,0 +1,249 @@
+/// What an `Animator` does after the last frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationMode {
+    /// starts over from the first frame
+    #[default]
+    Loop,
+    /// plays backwards to the first frame, then forwards again
+    PingPong,
+    /// stops on the last frame
+    Once,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationFrame {
+    /// the index of the frame in the atlas
+    pub index: usize,
+    /// in seconds
+    pub duration: f32,
+    /// reported by `Animator::update` when the frame is shown
+    pub event: Option<String>,
+}
+
+/// A sequence of `GGAtlas` frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GGAnimation {
+    pub name: String,
+    pub frames: Vec<AnimationFrame>,
+    pub mode: AnimationMode,
+}
+
+impl GGAnimation {
+    /// creates an animation from atlas frame indices and their durations in seconds
+    pub fn new(name: impl Into<String>, frames: impl IntoIterator<Item = (usize, f32)>, mode: AnimationMode) -> Self {
+        Self {
+            name: name.into(),
+            frames: frames
+                .into_iter()
+                .map(|(index, duration)| AnimationFrame {
+                    index,
+                    duration,
+                    event: None,
+                })
+                .collect(),
+            mode,
+        }
+    }
+
+    /// creates an animation from the frames of an animated tile in a tiled tileset
+    ///
+    /// the frame indices are tile ids, which index the atlas of the tileset
+    pub fn from_tiled(name: impl Into<String>, frames: &[tiled::Frame]) -> Self {
+        let frames = frames
+            .iter()
+            .map(|frame| (frame.tile_id as usize, frame.duration as f32 / 1000.0));
+        Self::new(name, frames, AnimationMode::Loop)
+    }
+
+    /// reports `event` whenever the frame at `position` in the animation is shown
+    pub fn with_event(mut self, position: usize, event: impl Into<String>) -> Self {
+        if let Some(frame) = self.frames.get_mut(position) {
+            frame.event = Some(event.into());
+        }
+        self
+    }
+
+    /// the length of one pass through the frames, in seconds
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+/// The animations of the animated tiles in `tileset`, named by their tile id.
+pub fn tileset_animations(tileset: &tiled::Tileset) -> Vec<(u32, GGAnimation)> {
+    let mut animations: Vec<_> = tileset
+        .tiles()
+        .filter_map(|(id, tile)| {
+            let frames = tile.animation.as_ref()?;
+            Some((id, GGAnimation::from_tiled(id.to_string(), frames)))
+        })
+        .collect();
+    animations.sort_by_key(|(id, _)| *id);
+    animations
+}
+
+/// Plays a `GGAnimation`, advanced with `update`.
+#[derive(Debug, Clone)]
+pub struct Animator {
+    animation: GGAnimation,
+    /// the position in `animation.frames`
+    position: usize,
+    time: f32,
+    backwards: bool,
+    started: bool,
+    finished: bool,
+    pub speed: f32,
+}
+
+impl Animator {
+    pub fn new(animation: GGAnimation) -> Self {
+        Self {
+            animation,
+            position: 0,
+            time: 0.0,
+            backwards: false,
+            started: false,
+            finished: false,
+            speed: 1.0,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// switches to `animation` from its start, unless it is already playing
+    pub fn play(&mut self, animation: &GGAnimation) {
+        if self.animation.name != animation.name {
+            self.animation = animation.clone();
+            self.restart();
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.position = 0;
+        self.time = 0.0;
+        self.backwards = false;
+        self.started = false;
+        self.finished = false;
+    }
+
+    pub fn animation(&self) -> &GGAnimation {
+        &self.animation
+    }
+
+    /// the index of the atlas frame to draw, if the animation has any frames
+    pub fn frame(&self) -> Option<usize> {
+        Some(self.animation.frames.get(self.position)?.index)
+    }
+
+    /// the position of the current frame in the animation
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// returns true once an `AnimationMode::Once` animation reached its last frame
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// advances the animation by `dt` seconds, returning the events of the frames shown
+    pub fn update(&mut self, dt: f32) -> Vec<String> {
+        let mut events = Vec::new();
+        let len = self.animation.frames.len();
+        if len == 0 {
+            return events;
+        }
+        if !self.started {
+            self.started = true;
+            events.extend(self.animation.frames[self.position].event.clone());
+        }
+        // an animation without duration would never leave its frames
+        if self.finished || self.animation.duration() <= 0.0 {
+            return events;
+        }
+        self.time += dt * self.speed;
+        while self.time >= self.animation.frames[self.position].duration {
+            self.time -= self.animation.frames[self.position].duration;
+            let last = len - 1;
+            self.position = match self.animation.mode {
+                AnimationMode::Loop => (self.position + 1) % len,
+                AnimationMode::Once if self.position == last => {
+                    self.finished = true;
+                    self.time = 0.0;
+                    break;
+                }
+                AnimationMode::Once => self.position + 1,
+                AnimationMode::PingPong if len == 1 => 0,
+                AnimationMode::PingPong => {
+                    if self.position == last {
+                        self.backwards = true;
+                    } else if self.position == 0 {
+                        self.backwards = false;
+                    }
+                    match self.backwards {
+                        true => self.position - 1,
+                        false => self.position + 1,
+                    }
+                }
+            };
+            events.extend(self.animation.frames[self.position].event.clone());
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AnimationMode, Animator, GGAnimation};
+
+    fn frames(animator: &mut Animator, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                animator.update(0.1);
+                animator.frame().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_animator() {
+        let frames_of = |mode| GGAnimation::new("walk", [(4, 0.1), (5, 0.1), (6, 0.1)], mode);
+
+        let mut looping = Animator::new(frames_of(AnimationMode::Loop));
+        assert_eq!(looping.frame(), Some(4));
+        assert_eq!(frames(&mut looping, 4), [5, 6, 4, 5]);
+
+        let mut ping_pong = Animator::new(frames_of(AnimationMode::PingPong));
+        assert_eq!(frames(&mut ping_pong, 6), [5, 6, 5, 4, 5, 6]);
+
+        let mut once = Animator::new(frames_of(AnimationMode::Once));
+        assert_eq!(frames(&mut once, 4), [5, 6, 6, 6]);
+        assert!(once.is_finished());
+        once.restart();
+        assert_eq!(once.frame(), Some(4));
+        assert!(!once.is_finished());
+    }
+
+    #[test]
+    fn test_animation_events() {
+        let animation = GGAnimation::new("attack", [(0, 0.25), (1, 0.25), (2, 0.5)], AnimationMode::Loop)
+            .with_event(0, "swing")
+            .with_event(2, "hit");
+        assert_eq!(animation.duration(), 1.0);
+
+        let mut animator = Animator::new(animation.clone());
+        assert_eq!(animator.update(0.125), ["swing"]);
+        assert!(animator.update(0.25).is_empty());
+        assert_eq!(animator.update(0.25), ["hit"]);
+        // a long step shows several frames
+        assert_eq!(animator.update(0.875), ["swing", "hit"]);
+
+        // playing the same animation again does not restart it
+        animator.play(&animation);
+        assert_eq!(animator.frame(), Some(2));
+        animator.play(&GGAnimation::new("idle", [(7, 0.5)], AnimationMode::Loop));
+        assert_eq!(animator.frame(), Some(7));
+    }
+}
@@ -1,15 +1,22 @@
+use std::sync::Arc;
+
 use eframe::{egui, egui_glow, glow};
 use crate::GAssets;
 
 pub struct InitContext<'a> {
     pub assets: &'a mut GAssets,
-    pub gl:&'a glow::Context,
+    /// `None` when running headless
+    pub gl:Option<&'a Arc<glow::Context>>,
 }
 
 pub struct PaintGlowContext<'a> {
     pub dt:f32,
+    /// how far the simulation is into the next fixed update, between 0.0 and 1.0
+    pub alpha:f32,
     pub assets: &'a mut GAssets,
     pub painter:&'a egui_glow::Painter,
+    /// the viewport and pixels per point of the paint callback
+    pub info:&'a egui::PaintCallbackInfo,
 }
 
 pub struct UpdateContext<'a> {
@@ -17,8 +24,15 @@ pub struct UpdateContext<'a> {
     pub egui_ctx: &'a egui::Context,
     pub rhai_engine: &'a mut rhai::Engine,
     pub rhai_ast: &'a rhai::AST,
-    pub audio_manager:&'a mut kira::AudioManager,
+    /// `None` when no audio device is available, e.g. when running headless
+    pub audio_manager:Option<&'a mut kira::AudioManager>,
     pub dt:f32,
+    /// how far the simulation is into the next fixed update, between 0.0 and 1.0
+    ///
+    /// always 0.0 during `fixed_update`
+    pub alpha:f32,
+    /// the `dt` of `fixed_update`, `None` when fixed updates are disabled
+    pub fixed_dt:Option<f32>,
 }
 
 pub trait GGApp {
@@ -28,12 +42,20 @@ pub trait GGApp {
     /// happens every frame, after paint_glow
     fn update(&mut self, g: UpdateContext);
 
+    /// happens zero or more times every frame before update_glow, with a fixed `dt`
+    /// given by `GGRunOptions::fixed_update_rate`
+    fn fixed_update(&mut self, g: UpdateContext) {
+        let _ = g;
+    }
+
     /// happens every frame before paint_glow
     fn update_glow(&mut self, g: UpdateContext) {
         let _ = g;
     }
 
     /// happens every frame to paint via glow, between update_glow and update
+    ///
+    /// never called when running headless
     fn paint_glow(&mut self, g:PaintGlowContext) {
         let _ = g;
     }
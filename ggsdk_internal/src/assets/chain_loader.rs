@@ -0,0 +1,137 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use crate::{AssetError, EmbeddedLoader, Loader, LoaderEvent};
+
+/// Creates a fresh `Loader`, called once for every asset type.
+pub type LoaderFactory = Arc<dyn Fn() -> Box<dyn Loader> + Send + Sync>;
+
+/// Where `GAssets` reads files from.
+///
+/// When several sources are given they are tried in order, and a file
+/// which fails to load from one source is requested from the next.
+#[derive(Clone)]
+pub enum AssetSource {
+    /// loose files, read from disk on native and fetched on the web
+    Files,
+    /// an `AssetPack` at the given path, fetched once and shared by all asset types
+    Pack(String),
+    /// files compiled into the binary, see `embed_assets!`
+    Embedded(EmbeddedLoader),
+    /// any other loader, e.g. a mock or a remote service
+    Custom(LoaderFactory),
+}
+
+impl AssetSource {
+    pub fn custom<L: Loader>(f: impl Fn() -> L + Send + Sync + 'static) -> Self {
+        AssetSource::Custom(Arc::new(move || Box::new(f())))
+    }
+}
+
+/// Requests each path from a list of loaders in turn until one of them loads it.
+///
+/// Reloads and progress are passed on from every loader.
+#[derive(Default)]
+pub struct ChainLoader {
+    loaders: Vec<Box<dyn Loader>>,
+    /// the index of the loader each pending path was last requested from
+    requests: HashMap<String, usize>,
+    failed: VecDeque<LoaderEvent>,
+}
+
+impl ChainLoader {
+    pub fn new(loaders: Vec<Box<dyn Loader>>) -> Self {
+        Self {
+            loaders,
+            ..Default::default()
+        }
+    }
+
+    /// adds a loader to try after the ones already added
+    pub fn with(mut self, loader: impl Loader) -> Self {
+        self.loaders.push(Box::new(loader));
+        self
+    }
+}
+
+impl Loader for ChainLoader {
+    fn request(&mut self, path: String) {
+        match self.loaders.first_mut() {
+            Some(loader) => {
+                self.requests.insert(path.clone(), 0);
+                loader.request(path);
+            }
+            None => self.failed.push_back(LoaderEvent::LoadFailed(path, AssetError::NotFound)),
+        }
+    }
+
+    fn poll(&mut self) -> Option<LoaderEvent> {
+        if let Some(e) = self.failed.pop_front() {
+            return Some(e);
+        }
+        for i in 0..self.loaders.len() {
+            while let Some(e) = self.loaders[i].poll() {
+                match e {
+                    LoaderEvent::LoadFailed(path, err)
+                        if self.requests.get(&path) == Some(&i) && i + 1 < self.loaders.len() =>
+                    {
+                        tracing::debug!("{path} not loaded from source {i}, trying the next: {err}");
+                        self.requests.insert(path.clone(), i + 1);
+                        self.loaders[i + 1].request(path);
+                    }
+                    LoaderEvent::Load(ref path, _) | LoaderEvent::LoadFailed(ref path, _) => {
+                        if self.requests.get(path) == Some(&i) {
+                            self.requests.remove(path);
+                        }
+                        return Some(e);
+                    }
+                    LoaderEvent::Reload(..) | LoaderEvent::Progress(..) => return Some(e),
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChainLoader;
+    use crate::{AssetError, EmbeddedLoader, Loader, LoaderEvent};
+
+    #[test]
+    fn test_chain_loader() {
+        let first = EmbeddedLoader::new(&[("a.txt", b"first a")]);
+        let second = EmbeddedLoader::new(&[("a.txt", b"second a"), ("b.txt", b"second b")]);
+        let mut loader = ChainLoader::default().with(first).with(second);
+        loader.request("a.txt".into());
+        loader.request("b.txt".into());
+        loader.request("c.txt".into());
+
+        let mut events = Vec::new();
+        while let Some(e) = loader.poll() {
+            events.push(e);
+        }
+        let events: Vec<_> = events
+            .into_iter()
+            .map(|e| match e {
+                LoaderEvent::Load(path, data) => (path, Ok(data)),
+                LoaderEvent::LoadFailed(path, err) => (path, Err(err)),
+                e => panic!("unexpected event {e:?}"),
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("a.txt".to_string(), Ok(b"first a".to_vec())),
+                ("b.txt".to_string(), Ok(b"second b".to_vec())),
+                ("c.txt".to_string(), Err(AssetError::NotFound)),
+            ]
+        );
+
+        let mut empty = ChainLoader::default();
+        empty.request("a.txt".into());
+        assert!(matches!(empty.poll(), Some(LoaderEvent::LoadFailed(_, AssetError::NotFound))));
+    }
+}
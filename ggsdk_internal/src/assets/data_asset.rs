@@ -0,0 +1,129 @@
+use std::ops::{Deref, DerefMut};
+
+use serde::de::DeserializeOwned;
+
+use crate::AssetError;
+
+use super::{GAssets, Handle, Load, PollContext};
+
+/// Data deserialized from a JSON file, see `GAssets::load_json`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonAsset<T>(pub T);
+
+/// Data deserialized from a RON file, see `GAssets::load_ron`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RonAsset<T>(pub T);
+
+impl<T> Deref for JsonAsset<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for JsonAsset<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> Deref for RonAsset<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for RonAsset<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+fn decode_json<T: DeserializeOwned>(load: Load, _: &mut PollContext) -> Result<JsonAsset<T>, AssetError> {
+    Ok(JsonAsset(serde_json::from_slice(&load.data)?))
+}
+
+fn decode_ron<T: DeserializeOwned>(load: Load, _: &mut PollContext) -> Result<RonAsset<T>, AssetError> {
+    ron::de::from_bytes(&load.data).map(RonAsset).map_err(|err| AssetError::Parse {
+        line: err.position.line,
+        column: err.position.col,
+        message: err.code.to_string(),
+    })
+}
+
+impl GAssets {
+    /// loads the JSON file at `path` deserialized as `T`
+    pub fn load_json<T: DeserializeOwned + Clone + 'static>(&mut self, path: &str, name: &str) -> Handle<JsonAsset<T>> {
+        if !self.can_decode::<JsonAsset<T>>() {
+            self.register_decoder(decode_json::<T>).expect("json assets have no built in decoder");
+        }
+        self.load(path, name)
+    }
+
+    /// loads the RON file at `path` deserialized as `T`
+    pub fn load_ron<T: DeserializeOwned + Clone + 'static>(&mut self, path: &str, name: &str) -> Handle<RonAsset<T>> {
+        if !self.can_decode::<RonAsset<T>>() {
+            self.register_decoder(decode_ron::<T>).expect("ron assets have no built in decoder");
+        }
+        self.load(path, name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use serde::Deserialize;
+
+    use crate::{
+        headless::fixture::{hot_reload, touch},
+        AssetError, GGHeadless, JsonAsset,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    struct Enemy {
+        name: String,
+        health: u32,
+    }
+
+    #[test]
+    fn test_data_assets() {
+        let dir = std::env::temp_dir().join(format!("ggsdk_data_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("enemies.json"), r#"[{ "name": "slime", "health": 3 }]"#).unwrap();
+        std::fs::write(dir.join("broken.json"), "[\n  { \"name\": \"slime\",\n    \"health\": \"3\" }\n]").unwrap();
+        std::fs::write(dir.join("boss.ron"), r#"(name: "dragon", health: 100)"#).unwrap();
+        std::fs::write(dir.join("broken.ron"), "(\n  name: \"dragon\",\n  health: -1,\n)").unwrap();
+
+        let files = dir.clone();
+        let mut headless = hot_reload(move |assets| {
+            let path = |file: &str| files.join(file).to_string_lossy().to_string();
+            assets.load_json::<Vec<Enemy>>(&path("enemies.json"), "enemies");
+            assets.load_json::<Vec<Enemy>>(&path("broken.json"), "broken");
+            assets.load_ron::<Enemy>(&path("boss.ron"), "boss");
+            assets.load_ron::<Enemy>(&path("broken.ron"), "broken_ron");
+        });
+        {
+            let assets = headless.assets();
+            let enemies = assets.get::<JsonAsset<Vec<Enemy>>>("enemies").unwrap();
+            assert_eq!(enemies[0], Enemy { name: "slime".to_string(), health: 3 });
+            assert!(matches!(
+                assets.error::<JsonAsset<Vec<Enemy>>>("broken"),
+                Some(AssetError::Parse { line: 3, message, .. }) if message.contains("invalid type")
+            ));
+            assert_eq!(assets.get::<crate::RonAsset<Enemy>>("boss").unwrap().health, 100);
+            assert!(matches!(assets.error::<crate::RonAsset<Enemy>>("broken_ron"), Some(AssetError::Parse { line: 3, .. })));
+        }
+
+        let path = dir.join("enemies.json");
+        std::fs::write(&path, r#"[{ "name": "bat", "health": 1 }]"#).unwrap();
+        touch(&path);
+        let reloaded = |h: &mut GGHeadless<_>| h.assets().get::<JsonAsset<Vec<Enemy>>>("enemies").unwrap()[0].name == "bat";
+        assert!(headless.step_until(reloaded, Duration::from_secs(10)), "asset was not reloaded");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,60 +1,109 @@
-use crate::{Loader, LoaderEvent};
+use crate::{
+    assets::pool::{default_workers, WorkerPool},
+    Loader, LoaderEvent,
+};
 use std::{
-    collections::VecDeque,
+    collections::HashMap,
     fs,
-    path::Path,
-    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
 };
+use web_time::Instant;
 
-#[derive(Default)]
+/// Reads files from disk on a pool of worker threads.
 pub struct DiskLoader {
-    requests: VecDeque<String>,
-    load_handle: Option<JoinHandle<LoaderEvent>>,
+    pool: WorkerPool<LoaderEvent>,
+    watch: Option<Watch>,
+}
+
+impl Default for DiskLoader {
+    fn default() -> Self {
+        Self {
+            pool: WorkerPool::new(default_workers()),
+            watch: None,
+        }
+    }
+}
+
+/// Modification times of loaded files, used to detect changes in watch mode.
+struct Watch {
+    modified: HashMap<String, SystemTime>,
+    last_check: Instant,
+}
+
+impl Watch {
+    const INTERVAL: Duration = Duration::from_millis(500);
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn read(path: String, reload: bool) -> LoaderEvent {
+    match fs::read(&path) {
+        Ok(data) if reload => LoaderEvent::Reload(path, data),
+        Ok(data) => LoaderEvent::Load(path, data),
+        Err(err) => LoaderEvent::LoadFailed(path, err.into()),
+    }
+}
+
+impl DiskLoader {
+    /// when `watch` is set, `LoaderEvent::Reload` is emitted whenever a loaded file changes on disk
+    pub fn with_watch(mut self, watch: bool) -> Self {
+        self.watch = watch.then(|| Watch {
+            modified: Default::default(),
+            last_check: Instant::now(),
+        });
+        self
+    }
+
+    /// sets the number of files read in parallel
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.pool = WorkerPool::new(workers);
+        self
+    }
+
+    fn check_modified(&mut self) {
+        let Some(watch) = &mut self.watch else {
+            return;
+        };
+        if watch.last_check.elapsed() < Watch::INTERVAL {
+            return;
+        }
+        watch.last_check = Instant::now();
+        for (path, last_modified) in watch.modified.iter_mut() {
+            let Some(modified) = modified(path) else {
+                continue;
+            };
+            if modified != *last_modified {
+                *last_modified = modified;
+                let path = path.clone();
+                self.pool.spawn(move || read(path, true));
+            }
+        }
+    }
 }
 
 impl Loader for DiskLoader {
     fn request(&mut self, path: String) {
-        self.requests.push_back(path);
+        self.pool.spawn(move || read(path, false));
     }
 
     fn poll(&mut self) -> Option<LoaderEvent> {
-        match &self.load_handle {
-            Some(handle) => {
-                if handle.is_finished() {
-                    let handle = self.load_handle.take().unwrap();
-                    match handle.join() {
-                        Ok(e) => {
-                            return Some(e);
-                        }
-                        Err(_) => {
-                            return Some(LoaderEvent::LoadFailed("unknown error".to_string()));
-                        }
-                    }
-                }
-            }
-            None => {
-                if let Some(s) = self.requests.pop_front() {
-                    let handle: JoinHandle<LoaderEvent> = thread::spawn(move || {
-                        let path = Path::new(&s);
-                        
-                        match fs::read(path) {
-                            Ok(data) => LoaderEvent::Load(s.clone(), data),
-                            Err(_) => LoaderEvent::LoadFailed(s.clone()),
-                        }
-                    });
-                    self.load_handle = Some(handle);
-                }
-            }
+        self.check_modified();
+        let e = self.pool.try_recv()?;
+        if let (Some(watch), LoaderEvent::Load(path, _)) = (&mut self.watch, &e)
+            && let Some(modified) = modified(path)
+        {
+            watch.modified.insert(path.clone(), modified);
         }
-
-        None
+        Some(e)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::DiskLoader;
-    use crate::{Loader, LoaderEvent};
+    use crate::{AssetError, Loader, LoaderEvent};
 
     #[test]
     fn test_disk_assets_io() {
@@ -77,18 +126,46 @@ mod test {
                     "Data should not be empty for an existing file"
                 );
             }
-            LoaderEvent::LoadFailed(_) => panic!(),
+            _ => panic!(),
         }
 
         io.request("Not found.toml".into());
 
         match poll(&mut io) {
-            LoaderEvent::Load(..) => {
-                panic!();
-            }
-            LoaderEvent::LoadFailed(path) => {
+            LoaderEvent::LoadFailed(path, err) => {
                 assert_eq!(path, "Not found.toml");
+                assert_eq!(err, AssetError::NotFound);
             },
+            _ => {
+                panic!();
+            }
+        }
+    }
+
+    #[test]
+    fn test_disk_loader_concurrent() {
+        let mut io = DiskLoader::default().with_workers(3);
+        let mut paths = vec![
+            "src/lib.rs".to_string(),
+            "src/app.rs".to_string(),
+            "src/engine.rs".to_string(),
+            "src/atlas.rs".to_string(),
+            "Cargo.toml".to_string(),
+        ];
+        for path in paths.iter() {
+            io.request(path.clone());
+        }
+
+        let mut loaded = Vec::new();
+        while loaded.len() < paths.len() {
+            match io.poll() {
+                Some(LoaderEvent::Load(path, _)) => loaded.push(path),
+                Some(e) => panic!("unexpected event {e:?}"),
+                None => {}
+            }
         }
+        loaded.sort();
+        paths.sort();
+        assert_eq!(loaded, paths);
     }
 }
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{AssetError, Loader, LoaderEvent};
+
+/// Serves requests from files compiled into the binary.
+///
+/// Usually created with `embed_assets!`, which embeds a directory, or from a
+/// table of `include_bytes!` with `EmbeddedLoader::new`.
+#[derive(Clone, Default)]
+pub struct EmbeddedLoader {
+    files: HashMap<String, &'static [u8]>,
+    events: VecDeque<LoaderEvent>,
+}
+
+impl EmbeddedLoader {
+    pub fn new(files: &'static [(&'static str, &'static [u8])]) -> Self {
+        Self {
+            files: files
+                .iter()
+                .map(|(path, data)| (path.to_string(), *data))
+                .collect(),
+            events: Default::default(),
+        }
+    }
+
+    /// embeds every file below `dir`, each loaded with its path joined onto `root`
+    pub fn from_dir(dir: &'static include_dir::Dir<'static>, root: &str) -> Self {
+        let root = root.trim_end_matches('/');
+        let mut files = HashMap::new();
+        let mut dirs = vec![dir];
+        while let Some(dir) = dirs.pop() {
+            dirs.extend(dir.dirs());
+            for file in dir.files() {
+                let path = file.path().to_string_lossy().replace('\\', "/");
+                let path = match root.is_empty() {
+                    true => path,
+                    false => format!("{root}/{path}"),
+                };
+                files.insert(path, file.contents());
+            }
+        }
+        Self {
+            files,
+            events: Default::default(),
+        }
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(path.trim_start_matches("./"))
+    }
+}
+
+impl Loader for EmbeddedLoader {
+    fn request(&mut self, path: String) {
+        let e = match self.files.get(path.trim_start_matches("./")) {
+            Some(data) => LoaderEvent::Load(path, data.to_vec()),
+            None => LoaderEvent::LoadFailed(path, AssetError::NotFound),
+        };
+        self.events.push_back(e);
+    }
+
+    fn poll(&mut self) -> Option<LoaderEvent> {
+        self.events.pop_front()
+    }
+}
+
+/// Creates an `EmbeddedLoader` with every file below a directory compiled into the binary.
+///
+/// The directory is given as for `include_dir!`, and files are loaded with
+/// their path joined onto the optional root:
+///
+/// `embed_assets!("$CARGO_MANIFEST_DIR/assets", "assets")` embeds the `assets`
+/// directory of the calling crate, so `assets/sfx/coin.mp3` loads as it would from disk.
+#[macro_export]
+macro_rules! embed_assets {
+    ($dir:tt) => {
+        $crate::embed_assets!($dir, "")
+    };
+    ($dir:tt, $root:expr) => {{
+        use $crate::include_dir;
+        static DIR: include_dir::Dir<'static> = include_dir::include_dir!($dir);
+        $crate::EmbeddedLoader::from_dir(&DIR, $root)
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::EmbeddedLoader;
+    use crate::{AssetError, Loader, LoaderEvent};
+
+    static FILES: &[(&str, &[u8])] = &[("assets/a.txt", b"a"), ("lib.rs", include_bytes!("../lib.rs"))];
+
+    #[test]
+    fn test_embedded_loader() {
+        let mut loader = EmbeddedLoader::new(FILES);
+        loader.request("./assets/a.txt".into());
+        loader.request("missing.txt".into());
+
+        match loader.poll() {
+            Some(LoaderEvent::Load(path, data)) => {
+                assert_eq!(path, "./assets/a.txt");
+                assert_eq!(data, b"a");
+            }
+            e => panic!("unexpected event {e:?}"),
+        }
+        match loader.poll() {
+            Some(LoaderEvent::LoadFailed(path, err)) => {
+                assert_eq!(path, "missing.txt");
+                assert_eq!(err, AssetError::NotFound);
+            }
+            e => panic!("unexpected event {e:?}"),
+        }
+        assert!(loader.poll().is_none());
+
+        let loader = crate::embed_assets!("$CARGO_MANIFEST_DIR/src/assets", "src/assets");
+        assert!(loader.contains("src/assets/embedded_loader.rs"));
+        assert!(!loader.contains("src/lib.rs"));
+        let loader = crate::embed_assets!("$CARGO_MANIFEST_DIR/src/assets");
+        assert!(loader.contains("embedded_loader.rs"));
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetError {
+    NotFound,
+    PermissionDenied,
+    /// any other io error, with its message
+    Io(String),
+    /// the server responded with a non-success status code
+    Http(u16),
+    /// the request did not complete, e.g. a failed fetch
+    Network(String),
+    /// the data was read but could not be decoded into the asset type
+    Decode(String),
+    /// a file the asset depends on, e.g. the tileset of a map, failed to load
+    Dependency { path: String, error: Box<AssetError> },
+    /// the asset was unloaded, e.g. before it finished loading
+    Unloaded,
+    /// a text file could not be parsed, positions start at 1
+    Parse { line: usize, column: usize, message: String },
+    /// no decoder was registered for the asset type, see `GAssets::register_decoder`
+    NoDecoder(String),
+    /// the decoder of the asset type is built in and its assets were already loaded
+    DecoderInUse(String),
+}
+
+impl Display for AssetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetError::NotFound => write!(f, "not found"),
+            AssetError::PermissionDenied => write!(f, "permission denied"),
+            AssetError::Io(err) => write!(f, "io error: {err}"),
+            AssetError::Http(status) => write!(f, "http status {status}"),
+            AssetError::Network(err) => write!(f, "network error: {err}"),
+            AssetError::Decode(err) => write!(f, "decode error: {err}"),
+            AssetError::Dependency { path, error } => write!(f, "failed to load {path}: {error}"),
+            AssetError::Unloaded => write!(f, "unloaded"),
+            AssetError::Parse { line, column, message } => {
+                write!(f, "parse error at line {line} column {column}: {message}")
+            }
+            AssetError::NoDecoder(type_name) => write!(f, "no decoder for {type_name}"),
+            AssetError::DecoderInUse(type_name) => write!(f, "the decoder of {type_name} is already in use"),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+impl From<std::io::Error> for AssetError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => AssetError::NotFound,
+            std::io::ErrorKind::PermissionDenied => AssetError::PermissionDenied,
+            _ => AssetError::Io(err.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AssetError {
+    fn from(err: serde_json::Error) -> Self {
+        let message = err.to_string();
+        // the position is reported separately
+        let message = match message.rfind(" at line ") {
+            Some(i) => message[..i].to_string(),
+            None => message,
+        };
+        AssetError::Parse {
+            line: err.line(),
+            column: err.column(),
+            message,
+        }
+    }
+}
+
+/// An asset that failed to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetFailure {
+    pub name: String,
+    pub path: String,
+    pub error: AssetError,
+}
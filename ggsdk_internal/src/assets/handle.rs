@@ -0,0 +1,236 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{AssetError, GGAsset};
+
+use super::TypedAssets;
+
+pub(crate) enum HandleState<T> {
+    Pending,
+    Loaded(Weak<GGAsset<T>>),
+    Failed(AssetError),
+    Unloaded,
+}
+
+type LoadedCallback<T> = Box<dyn FnOnce(Rc<GGAsset<T>>)>;
+
+pub(crate) struct HandleSlot<T> {
+    state: HandleState<T>,
+    wakers: Vec<Waker>,
+    callbacks: Vec<LoadedCallback<T>>,
+}
+
+/// A typed reference to an asset, returned by `GAssets::load`.
+///
+/// Handles are cheap to clone and follow the asset as it is loaded, reloaded
+/// and unloaded. They do not keep the asset alive, use `get` for that.
+///
+/// Awaiting a handle resolves once the asset has loaded or failed to load:
+///
+/// `let atlas = g.assets.load::<GGAtlas>("smilie.png", "smilie").await?;`
+pub struct Handle<T> {
+    name: Rc<str>,
+    slot: Rc<RefCell<HandleSlot<T>>>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T> Handle<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// returns the asset if it is loaded
+    pub fn get(&self) -> Option<Rc<GGAsset<T>>> {
+        match &self.slot.borrow().state {
+            HandleState::Loaded(asset) => asset.upgrade(),
+            _ => None,
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.get().is_some()
+    }
+
+    /// returns false while the asset is queued or loading
+    pub fn is_done(&self) -> bool {
+        !matches!(self.slot.borrow().state, HandleState::Pending)
+    }
+
+    /// returns why the asset failed to load, if it did
+    pub fn error(&self) -> Option<AssetError> {
+        match &self.slot.borrow().state {
+            HandleState::Failed(error) => Some(error.clone()),
+            _ => None,
+        }
+    }
+
+    /// calls `f` with the asset once it is loaded, right away if it already is
+    ///
+    /// `f` is not called if the asset fails to load, unless it is loaded again later.
+    pub fn when_loaded(&self, f: impl FnOnce(Rc<GGAsset<T>>) + 'static) {
+        match self.get() {
+            Some(asset) => f(asset),
+            None => self.slot.borrow_mut().callbacks.push(Box::new(f)),
+        }
+    }
+}
+
+impl<T> Future for Handle<T> {
+    type Output = Result<Rc<GGAsset<T>>, AssetError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.borrow_mut();
+        match &slot.state {
+            HandleState::Pending => {
+                // a task polling the same handle again is only woken once
+                if !slot.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    slot.wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+            HandleState::Loaded(asset) => Poll::Ready(asset.upgrade().ok_or(AssetError::Unloaded)),
+            HandleState::Failed(error) => Poll::Ready(Err(error.clone())),
+            HandleState::Unloaded => Poll::Ready(Err(AssetError::Unloaded)),
+        }
+    }
+}
+
+impl<T: 'static> TypedAssets<T> {
+    /// returns a handle to the asset with the given name
+    pub fn handle(&mut self, name: &str) -> Handle<T> {
+        if let Some(slot) = self.handles.get(name).and_then(Weak::upgrade) {
+            return Handle {
+                name: name.into(),
+                slot,
+            };
+        }
+        self.handles.retain(|_, slot| slot.strong_count() > 0);
+        let state = match self.loaded.get(name) {
+            Some(asset) => HandleState::Loaded(Rc::downgrade(asset)),
+            None => HandleState::Pending,
+        };
+        let slot = Rc::new(RefCell::new(HandleSlot {
+            state,
+            wakers: Vec::new(),
+            callbacks: Vec::new(),
+        }));
+        self.handles.insert(name.to_string(), Rc::downgrade(&slot));
+        Handle {
+            name: name.into(),
+            slot,
+        }
+    }
+
+    /// updates the handles of `name`, waking the tasks awaiting them
+    pub(crate) fn set_handle_state(&mut self, name: &str, state: HandleState<T>) {
+        let Some(slot) = self.handles.get(name).and_then(Weak::upgrade) else {
+            return;
+        };
+        let asset = match &state {
+            HandleState::Loaded(asset) => asset.upgrade(),
+            _ => None,
+        };
+        let (wakers, callbacks) = {
+            let mut slot = slot.borrow_mut();
+            slot.state = state;
+            let wakers = std::mem::take(&mut slot.wakers);
+            let callbacks = match asset {
+                Some(_) => std::mem::take(&mut slot.callbacks),
+                None => Vec::new(),
+            };
+            (wakers, callbacks)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+        if let Some(asset) = asset {
+            for callback in callbacks {
+                callback(asset.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::Cell,
+        future::Future,
+        pin::pin,
+        rc::Rc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+        time::Duration,
+    };
+
+    use crate::{headless::fixture::EmptyApp, AssetError, GGEngine};
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut headless = GGEngine::headless(EmptyApp, Default::default());
+        let (manifest, missing) = {
+            let mut assets = headless.assets();
+            (
+                assets.load::<String>("Cargo.toml", "manifest"),
+                assets.load::<String>("missing.txt", "missing"),
+            )
+        };
+        let called = Rc::new(Cell::new(false));
+        manifest.when_loaded({
+            let called = called.clone();
+            move |asset| called.set(asset.data.contains("ggsdk_internal"))
+        });
+        assert!(!manifest.is_done());
+
+        let wakes = Arc::new(CountingWaker::default());
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(manifest.clone());
+        for _ in 0..10 {
+            assert!(future.as_mut().poll(&mut cx).is_pending());
+        }
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+
+        assert!(called.get());
+        assert!(manifest.is_loaded());
+        assert_eq!(manifest.name(), "manifest");
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(Ok(asset)) => assert!(Rc::ptr_eq(&asset, &manifest.get().unwrap())),
+            _ => panic!("handle did not resolve"),
+        }
+        assert_eq!(missing.error(), Some(AssetError::NotFound));
+        assert!(matches!(pin!(missing).poll(&mut cx), Poll::Ready(Err(AssetError::NotFound))));
+
+        // loading the same asset again returns a handle to it
+        let again = headless.assets().load::<String>("Cargo.toml", "manifest");
+        assert!(again.is_loaded());
+
+        assert!(headless.assets().unload::<String>("manifest"));
+        assert!(!manifest.is_loaded());
+        assert!(matches!(pin!(again).poll(&mut cx), Poll::Ready(Err(AssetError::Unloaded))));
+    }
+}
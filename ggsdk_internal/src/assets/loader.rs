@@ -1,10 +1,16 @@
 #[cfg(test)]
 use mockall::automock;
 
-#[derive(Debug)]
+use crate::AssetError;
+
+#[derive(Debug, Clone)]
 pub enum LoaderEvent {
     Load(String, Vec<u8>),
-    LoadFailed(String)
+    LoadFailed(String, AssetError),
+    /// a previously loaded path changed and was read again
+    Reload(String, Vec<u8>),
+    /// bytes of a requested path received so far, and the size of the file if known
+    Progress(String, u64, Option<u64>)
 }
 
 #[cfg_attr(test, automock)]
@@ -12,3 +18,13 @@ pub trait Loader : 'static {
     fn request(&mut self, path:String);
     fn poll(&mut self) -> Option<LoaderEvent>;
 }
+
+impl Loader for Box<dyn Loader> {
+    fn request(&mut self, path:String) {
+        self.as_mut().request(path)
+    }
+
+    fn poll(&mut self) -> Option<LoaderEvent> {
+        self.as_mut().poll()
+    }
+}
@@ -1,4 +1,6 @@
 mod loader;
+mod error;
+pub use error::*;
 use eframe::egui;
 use kira::sound::static_sound::StaticSoundData;
 pub use loader::*;
@@ -6,16 +8,43 @@ pub use loader::*;
 #[cfg(not(target_arch = "wasm32"))]
 mod disk_loader;
 #[cfg(not(target_arch = "wasm32"))]
-use disk_loader::DiskLoader;
+pub use disk_loader::DiskLoader;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod pool;
+#[cfg(not(target_arch = "wasm32"))]
+use pool::{default_workers, WorkerPool};
+
+mod pack;
+pub use pack::*;
+
+mod embedded_loader;
+pub use embedded_loader::EmbeddedLoader;
+
+mod chain_loader;
+pub use chain_loader::*;
+
+mod handle;
+pub use handle::Handle;
+use handle::HandleState;
+
+mod data_asset;
+pub use data_asset::*;
+
+mod shader;
+pub use shader::*;
+
+mod tiled_map;
+pub use tiled_map::{image_atlas_name, tileset_atlas_name};
 
 #[cfg(target_arch = "wasm32")]
 mod web_loader;
 #[cfg(target_arch = "wasm32")]
 pub use web_loader::*;
 
-use std::{any::{Any, TypeId}, collections::HashMap, io::Cursor, ops::{Deref, DerefMut}, path::Path, rc::Rc, str::from_utf8};
+use std::{any::{Any, TypeId}, collections::{HashMap, HashSet}, ops::{Deref, DerefMut}, path::Path, rc::{Rc, Weak}, str::from_utf8, sync::Arc};
 
-use crate::GGAtlas;
+use crate::{AtlasFrame, AtlasGrid, AtlasMeta, GGAnimation, GGAtlas};
 
 #[derive(Clone)]
 pub struct GGAsset<T> {
@@ -40,13 +69,110 @@ impl<T> DerefMut for GGAsset<T> {
 
 pub enum AssetEvent<T> {
     Loaded(Rc<GGAsset<T>>),
-    LoadFailed { name: String, path: String },
+    LoadFailed(AssetFailure),
+    /// the file of a loaded asset changed and the asset was replaced
+    Reloaded(Rc<GGAsset<T>>),
+    /// the file of a loaded asset changed but could not be loaded, the old asset is kept
+    ReloadFailed(AssetFailure),
+    /// part of the file of a queued asset was received, `total` is known when the size of the file is
+    Progress { name: String, bytes: u64, total: Option<u64> },
+}
+
+/// Untyped counterpart of `AssetEvent`, as returned by `AssetLoader::poll`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetChange {
+    Loaded(String),
+    LoadFailed(AssetFailure),
+    Reloaded(String),
+    ReloadFailed(AssetFailure),
+    Progress { name: String, bytes: u64, total: Option<u64> },
+}
+
+impl<T> From<AssetEvent<T>> for AssetChange {
+    fn from(e: AssetEvent<T>) -> Self {
+        match e {
+            AssetEvent::Loaded(asset) => AssetChange::Loaded(asset.name.clone()),
+            AssetEvent::LoadFailed(failure) => AssetChange::LoadFailed(failure),
+            AssetEvent::Reloaded(asset) => AssetChange::Reloaded(asset.name.clone()),
+            AssetEvent::ReloadFailed(failure) => AssetChange::ReloadFailed(failure),
+            AssetEvent::Progress { name, bytes, total } => AssetChange::Progress { name, bytes, total },
+        }
+    }
+}
+
+/// Where an asset is in the process of loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetStatus {
+    /// requested from the loader, waiting for its file
+    Queued,
+    /// the file was received and is being decoded or waits for its dependencies
+    Loading,
+    Loaded,
+    Failed,
+}
+
+/// The number of `AssetNotice`s `GAssets` keeps until they are drained.
+pub const MAX_ASSET_EVENTS: usize = 1024;
+
+/// An `AssetChange` along with the type of the asset, see `GAssets::drain_events`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetNotice {
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub change: AssetChange,
 }
 
 pub struct TypedAssets<T> {
     loaded: HashMap<String, Rc<GGAsset<T>>>,
-    pending: HashMap<String, String>,
+    /// names of the assets waiting for each requested path
+    pending: HashMap<String, Vec<String>>,
+    /// paths of the assets whose file was received but which are not loaded yet
+    loading: HashMap<String, String>,
+    failed: HashSet<String>,
+    /// events to return before polling the loader again
+    events: Vec<AssetEvent<T>>,
     loader: Box<dyn Loader>,
+    #[cfg(not(target_arch = "wasm32"))]
+    decoder: WorkerPool<Decoded>,
+    /// names of assets unloaded while being decoded, their results are dropped
+    #[cfg(not(target_arch = "wasm32"))]
+    cancelled: HashSet<String>,
+    /// files requested by assets in addition to their own, `None` while loading
+    dependencies: HashMap<String, Option<Result<Vec<u8>, AssetError>>>,
+    /// paths of the assets which requested each dependency
+    needed_by: HashMap<String, HashSet<String>>,
+    /// paths read again because a dependency of theirs changed, loaded as reloads
+    reloads: HashSet<String>,
+    /// loads waiting for dependencies, retried whenever one arrives
+    waiting: Vec<(Load, bool)>,
+    retry: Vec<(Load, bool)>,
+    /// assets created along with the loaded ones, added to `GAssets` after polling
+    dependents: Vec<Dependent>,
+    handles: HashMap<String, Weak<std::cell::RefCell<handle::HandleSlot<T>>>>,
+}
+
+/// Adds an asset created along with another to `GAssets`, see `AssetLoader::take_dependents`.
+pub type Dependent = Box<dyn FnOnce(&mut GAssets)>;
+
+/// The files requested with `Decode::Needs` which have been loaded.
+pub struct Dependencies<'a> {
+    files: &'a HashMap<String, Option<Result<Vec<u8>, AssetError>>>,
+}
+
+impl<'a> Dependencies<'a> {
+    pub fn get(&self, path: &str) -> Option<&'a [u8]> {
+        match self.files.get(path) {
+            Some(Some(Ok(data))) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+/// The result of decoding an asset which may depend on other files.
+pub enum Decode<T> {
+    Done(Result<T, AssetError>),
+    /// the files with these paths are needed to decode the asset
+    Needs(Vec<String>),
 }
 
 pub struct Load {
@@ -55,6 +181,15 @@ pub struct Load {
     pub data:Vec<u8>
 }
 
+/// The result of decoding a file on a worker thread.
+#[cfg(not(target_arch = "wasm32"))]
+struct Decoded {
+    name: String,
+    path: String,
+    reload: bool,
+    result: Result<Box<dyn Any + Send>, AssetError>,
+}
+
 impl<T : 'static> TypedAssets<T> {
     pub fn to_any(self) -> Box<dyn std::any::Any> {
         Box::new(self)
@@ -64,87 +199,632 @@ impl<T : 'static> TypedAssets<T> {
         Self {
             loaded: Default::default(),
             pending: Default::default(),
+            loading: Default::default(),
+            failed: Default::default(),
+            events: Default::default(),
             loader: Box::new(loader),
+            #[cfg(not(target_arch = "wasm32"))]
+            decoder: WorkerPool::new(default_workers()),
+            #[cfg(not(target_arch = "wasm32"))]
+            cancelled: Default::default(),
+            dependencies: Default::default(),
+            needed_by: Default::default(),
+            reloads: Default::default(),
+            waiting: Default::default(),
+            retry: Default::default(),
+            dependents: Default::default(),
+            handles: Default::default(),
         }
     }
-    pub fn load(&mut self, path: &str, name: &str) {
-        self.pending.insert(path.to_string(), name.to_string());
-        self.loader.request(path.into());
+
+    /// sets the number of files decoded in parallel by `poll_decoded`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_decode_workers(mut self, workers: usize) -> Self {
+        self.decoder = WorkerPool::new(workers);
+        self
+    }
+
+    /// requests the file at `path` and loads it as `name`
+    ///
+    /// does nothing when `name` is already loaded or loading from `path`, and
+    /// several names loading the same path share a single request
+    pub fn load(&mut self, path: &str, name: &str) -> Handle<T> {
+        let loaded = self.loaded.get(name).is_some_and(|asset| asset.path == path);
+        let loading = self.loading.get(name).is_some_and(|loading| loading == path);
+        let queued = self.pending.get(path).is_some_and(|names| names.iter().any(|n| n == name));
+        if loaded || loading || queued {
+            return self.handle(name);
+        }
+        // a name is loaded from a single path at a time
+        self.cancel(name);
+        self.failed.remove(name);
+        match self.pending.get_mut(path) {
+            Some(names) => names.push(name.to_string()),
+            None => {
+                self.pending.insert(path.to_string(), vec![name.to_string()]);
+                self.loader.request(path.into());
+            }
+        }
+        let handle = self.handle(name);
+        self.set_handle_state(name, HandleState::Pending);
+        handle
+    }
+
+    pub fn status(&self, name: &str) -> Option<AssetStatus> {
+        if self.loaded.contains_key(name) {
+            Some(AssetStatus::Loaded)
+        } else if self.loading.contains_key(name) {
+            Some(AssetStatus::Loading)
+        } else if self.pending.values().flatten().any(|n| n == name) {
+            Some(AssetStatus::Queued)
+        } else if self.failed.contains(name) {
+            Some(AssetStatus::Failed)
+        } else {
+            None
+        }
+    }
+
+    /// returns the number of assets which `status` reports with `status`
+    pub fn count(&self, status: AssetStatus) -> usize {
+        let loaded_or_loading = |name: &String| self.loaded.contains_key(name) || self.loading.contains_key(name);
+        match status {
+            AssetStatus::Loaded => self.loaded.len(),
+            AssetStatus::Loading => self.loading.keys().filter(|name| !self.loaded.contains_key(*name)).count(),
+            AssetStatus::Queued => self.pending.values().flatten().filter(|name| !loaded_or_loading(name)).count(),
+            // loading a name again clears its failure, so failed names are never queued
+            AssetStatus::Failed => self.failed.iter().filter(|name| !loaded_or_loading(name)).count(),
+        }
+    }
+
+    /// returns the status of every asset which was loaded or requested
+    pub fn statuses(&self) -> HashMap<&str, AssetStatus> {
+        let names = self
+            .loaded
+            .keys()
+            .chain(self.loading.keys())
+            .chain(self.pending.values().flatten())
+            .chain(self.failed.iter());
+        names
+            .filter_map(|name| Some((name.as_str(), self.status(name)?)))
+            .collect()
     }
    
     pub fn get(&self, name: &str) -> Option<Rc<GGAsset<T>>> {
         self.loaded.get(name).cloned()
     }
-    pub fn poll<F>(&mut self, f: F) -> Option<AssetEvent<T>>
+
+    pub fn iter(&self) -> impl Iterator<Item = &Rc<GGAsset<T>>> {
+        self.loaded.values()
+    }
+
+    /// adds an asset which was not loaded from a file, replacing any with the same name
+    pub fn insert(&mut self, name: &str, path: &str, data: T) -> Rc<GGAsset<T>> {
+        let asset = Rc::new(GGAsset {
+            name: name.to_string(),
+            path: path.to_string(),
+            data,
+        });
+        self.loaded.insert(name.to_string(), asset.clone());
+        self.set_handle_state(name, HandleState::Loaded(Rc::downgrade(&asset)));
+        asset
+    }
+
+    /// removes a loaded asset, it is freed once the returned and all other handles are dropped
+    pub fn unload(&mut self, name: &str) -> Option<Rc<GGAsset<T>>> {
+        let asset = self.loaded.remove(name)?;
+        self.set_handle_state(name, HandleState::Unloaded);
+        Some(asset)
+    }
+
+    /// cancels a load which has not completed yet, returns false if there was none
+    pub fn cancel(&mut self, name: &str) -> bool {
+        let mut cancelled = false;
+        for names in self.pending.values_mut() {
+            let len = names.len();
+            names.retain(|n| n != name);
+            cancelled |= names.len() != len;
+        }
+        // the loader may still deliver the file, it is then ignored
+        self.pending.retain(|_, names| !names.is_empty());
+        if self.loading.remove(name).is_some() {
+            let len = self.waiting.len() + self.retry.len();
+            self.waiting.retain(|(load, reload)| *reload || load.name != name);
+            self.retry.retain(|(load, reload)| *reload || load.name != name);
+            // otherwise the file is being decoded on a worker
+            #[cfg(not(target_arch = "wasm32"))]
+            if self.waiting.len() + self.retry.len() == len {
+                self.cancelled.insert(name.to_string());
+            }
+            cancelled = true;
+        }
+        if cancelled {
+            self.set_handle_state(name, HandleState::Unloaded);
+        }
+        cancelled
+    }
+
+    /// fails the asset `name` without requesting its file, reported on the next poll
+    pub fn fail(&mut self, path: &str, name: &str, error: AssetError) -> Handle<T> {
+        self.cancel(name);
+        self.failed.insert(name.to_string());
+        let handle = self.handle(name);
+        self.set_handle_state(name, HandleState::Failed(error.clone()));
+        let failure = AssetFailure { name: name.to_string(), path: path.to_string(), error };
+        self.events.insert(0, AssetEvent::LoadFailed(failure));
+        handle
+    }
+
+    /// forgets that the asset with the given name failed to load
+    pub fn clear_failed(&mut self, name: &str) -> bool {
+        self.failed.remove(name)
+    }
+
+    fn loaded_names(&self, path: &str) -> Vec<String> {
+        let mut names: Vec<_> = self.loaded.values().filter(|a| a.path == path).map(|a| a.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    /// splits the file at `path` into a load for each name, returning the first and queueing the rest
+    fn split_load(&mut self, names: Vec<String>, path: String, data: Vec<u8>, reload: bool) -> Option<(Load, bool)> {
+        let mut loads: Vec<_> = names
+            .into_iter()
+            .map(|name| {
+                if !reload {
+                    self.loading.insert(name.clone(), path.clone());
+                }
+                (Load { name, path: path.clone(), data: data.clone() }, reload)
+            })
+            .collect();
+        let first = loads.remove(0);
+        // `retry` is popped from the back
+        loads.reverse();
+        self.retry.append(&mut loads);
+        Some(first)
+    }
+
+    /// polls the loader for the next file, returning it along with whether it is a reload
+    fn next_load(&mut self) -> Option<Result<(Load, bool), AssetEvent<T>>> {
+        if let Some(load) = self.retry.pop() {
+            return Some(Ok(load));
+        }
+        if let Some(e) = self.events.pop() {
+            return Some(Err(e));
+        }
+        loop {
+            let e = self.loader.poll()?;
+            let (LoaderEvent::Load(path, _)
+            | LoaderEvent::LoadFailed(path, _)
+            | LoaderEvent::Reload(path, _)
+            | LoaderEvent::Progress(path, ..)) = &e;
+            if self.dependencies.contains_key(path) && !self.pending.contains_key(path) {
+                let changed = match e {
+                    LoaderEvent::Load(path, data) | LoaderEvent::Reload(path, data) => {
+                        self.dependencies.insert(path.clone(), Some(Ok(data))).flatten().is_some().then_some(path)
+                    }
+                    LoaderEvent::LoadFailed(path, error) => {
+                        self.dependencies.insert(path.clone(), Some(Err(error))).flatten().is_some().then_some(path)
+                    }
+                    LoaderEvent::Progress(..) => continue,
+                };
+                if let Some(path) = changed {
+                    self.reload_dependents(&path);
+                }
+                self.retry.append(&mut self.waiting);
+                if let Some(load) = self.retry.pop() {
+                    return Some(Ok(load));
+                }
+                continue;
+            }
+            match e {
+                LoaderEvent::Load(path, data) => {
+                    let reload = self.reloads.remove(&path);
+                    let Some(names) = self.pending.remove(&path) else {
+                        let names = self.loaded_names(&path);
+                        if !reload || names.is_empty() {
+                            continue;
+                        }
+                        return self.split_load(names, path, data, true).map(Ok);
+                    };
+                    return self.split_load(names, path, data, false).map(Ok);
+                }
+                LoaderEvent::Reload(path, data) => {
+                    // a fresh load of the same path is already on its way
+                    if self.pending.contains_key(&path) {
+                        continue;
+                    }
+                    let names = self.loaded_names(&path);
+                    if names.is_empty() {
+                        continue;
+                    }
+                    return self.split_load(names, path, data, true).map(Ok);
+                }
+                LoaderEvent::LoadFailed(path, error) => {
+                    self.reloads.remove(&path);
+                    let mut events: Vec<_> = match self.pending.remove(&path) {
+                        Some(names) => names
+                            .into_iter()
+                            .map(|name| {
+                                self.failed.insert(name.clone());
+                                self.set_handle_state(&name, HandleState::Failed(error.clone()));
+                                let failure = AssetFailure { name, path: path.clone(), error: error.clone() };
+                                AssetEvent::LoadFailed(failure)
+                            })
+                            .collect(),
+                        None => self
+                            .loaded_names(&path)
+                            .into_iter()
+                            .map(|name| {
+                                let failure = AssetFailure { name, path: path.clone(), error: error.clone() };
+                                AssetEvent::ReloadFailed(failure)
+                            })
+                            .collect(),
+                    };
+                    events.reverse();
+                    self.events.append(&mut events);
+                    if let Some(e) = self.events.pop() {
+                        return Some(Err(e));
+                    }
+                }
+                LoaderEvent::Progress(path, bytes, total) => {
+                    let Some(names) = self.pending.get(&path) else {
+                        continue;
+                    };
+                    let mut events: Vec<_> = names
+                        .iter()
+                        .rev()
+                        .map(|name| AssetEvent::Progress { name: name.clone(), bytes, total })
+                        .collect();
+                    self.events.append(&mut events);
+                    if let Some(e) = self.events.pop() {
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+    }
+
+    /// reads the assets which requested the dependency at `path` again, to decode them with its new contents
+    fn reload_dependents(&mut self, path: &str) {
+        let Some(paths) = self.needed_by.get(path) else {
+            return;
+        };
+        for path in paths {
+            let loaded = self.loaded.values().any(|asset| &asset.path == path);
+            if loaded && !self.pending.contains_key(path) && self.reloads.insert(path.clone()) {
+                self.loader.request(path.clone());
+            }
+        }
+    }
+
+    fn complete(&mut self, name: String, path: String, reload: bool, result: Result<T, AssetError>) -> Option<AssetEvent<T>> {
+        // the asset was unloaded while its file was read again
+        if reload && !self.loaded.contains_key(&name) {
+            return None;
+        }
+        if !reload {
+            self.loading.remove(&name);
+        }
+        let e = match result {
+            Ok(t) => {
+                let asset = Rc::new(GGAsset {
+                    name: name.clone(),
+                    path,
+                    data: t,
+                });
+                self.set_handle_state(&name, HandleState::Loaded(Rc::downgrade(&asset)));
+                self.loaded.insert(name, asset.clone());
+                match reload {
+                    true => AssetEvent::Reloaded(asset),
+                    false => AssetEvent::Loaded(asset),
+                }
+            }
+            Err(error) => {
+                if !reload {
+                    self.failed.insert(name.clone());
+                    self.set_handle_state(&name, HandleState::Failed(error.clone()));
+                }
+                let failure = AssetFailure { name, path, error };
+                match reload {
+                    true => AssetEvent::ReloadFailed(failure),
+                    false => AssetEvent::LoadFailed(failure),
+                }
+            }
+        };
+        Some(e)
+    }
+
+    /// decodes loaded files with `f` on the calling thread
+    pub fn poll<F>(&mut self, mut f: F) -> Option<AssetEvent<T>>
     where
-        F: Fn(Load) -> Result<T, ()>,
+        F: FnMut(Load) -> Result<T, AssetError>,
     {
-        if let Some(e) = self.loader.poll() {
-            let e: Option<AssetEvent<T>> = match e {
-                LoaderEvent::Load(path, vec) => match self.pending.remove(&path) {
-                    Some(name) => match f(Load { name: name.clone(), path: path.clone(), data: vec }) {
-                        Ok(t) => {
-                            let asset = Rc::new(GGAsset {
-                                name: name.clone(),
-                                path,
-                                data: t,
-                            });
-                            self.loaded.insert(name.clone(), asset.clone());
-                            Some(AssetEvent::Loaded(asset))
-                        }
-                        Err(_) => Some(AssetEvent::LoadFailed { name, path }),
-                    },
-                    None => None,
-                },
-                LoaderEvent::LoadFailed(path) => self.pending.remove(&path).map(|name| AssetEvent::LoadFailed { name, path }),
+        match self.next_load()? {
+            Ok((load, reload)) => {
+                let (name, path) = (load.name.clone(), load.path.clone());
+                let result = f(load);
+                self.complete(name, path, reload, result)
+            }
+            Err(e) => Some(e),
+        }
+    }
+
+    /// decodes loaded files with `f` on the calling thread, requesting the
+    /// files it needs through the same loader and trying again once they are loaded
+    pub fn poll_with_dependencies<F>(&mut self, f: F) -> Option<AssetEvent<T>>
+    where
+        F: Fn(&Load, &Dependencies) -> Decode<T>,
+    {
+        loop {
+            let (load, reload) = match self.next_load()? {
+                Ok(load) => load,
+                Err(e) => return Some(e),
+            };
+            let needs = match f(&load, &Dependencies { files: &self.dependencies }) {
+                Decode::Done(result) => return self.complete(load.name, load.path, reload, result),
+                Decode::Needs(needs) => needs,
             };
+            if let Some(error) = self.request_dependencies(&load.path, needs, reload) {
+                return self.complete(load.name, load.path, reload, Err(error));
+            }
+            self.waiting.push((load, reload));
+        }
+    }
 
-            return e;
+    /// requests the files in `needs` of the asset at `asset_path` which were not requested yet,
+    /// returning the error of the first one which failed to load
+    ///
+    /// files which failed to load are requested again when the asset is reloaded
+    fn request_dependencies(&mut self, asset_path: &str, needs: Vec<String>, reload: bool) -> Option<AssetError> {
+        let mut failed = None;
+        for path in needs {
+            self.needed_by.entry(path.clone()).or_default().insert(asset_path.to_string());
+            match self.dependencies.get(&path) {
+                Some(Some(Err(error))) if !reload => {
+                    failed.get_or_insert(AssetError::Dependency {
+                        path,
+                        error: Box::new(error.clone()),
+                    });
+                }
+                None | Some(Some(Err(_))) => {
+                    self.dependencies.insert(path.clone(), None);
+                    self.loader.request(path);
+                }
+                Some(_) => {}
+            }
         }
+        failed
+    }
 
-        None
+    /// decodes loaded files with `decode` on a worker thread, then turns the
+    /// result into `T` with `finish` on the calling thread
+    ///
+    /// on the web everything happens on the calling thread
+    pub fn poll_decoded<P, F>(&mut self, decode: fn(Load) -> Result<P, AssetError>, finish: F) -> Option<AssetEvent<T>>
+    where
+        P: Send + 'static,
+        F: Fn(&str, P) -> Result<T, AssetError>,
+    {
+        self.poll_decoded_with_dependencies(|_| Ok(Vec::new()), move |load, _| decode(load), finish)
+    }
+
+    /// like `poll_decoded`, but first requests the files `needs` returns for
+    /// a load and hands them to `decode` along with it, in the same order
+    pub fn poll_decoded_with_dependencies<P, N, D, F>(&mut self, needs: N, decode: D, finish: F) -> Option<AssetEvent<T>>
+    where
+        P: Send + 'static,
+        N: Fn(&Load) -> Result<Vec<String>, AssetError>,
+        D: Fn(Load, Vec<Vec<u8>>) -> Result<P, AssetError> + Clone + Send + 'static,
+        F: Fn(&str, P) -> Result<T, AssetError>,
+    {
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.poll_with_dependencies(|load, dependencies| {
+                let paths = match needs(load) {
+                    Ok(paths) => paths,
+                    Err(error) => return Decode::Done(Err(error)),
+                };
+                let files: Option<Vec<_>> = paths.iter().map(|path| dependencies.get(path).map(<[u8]>::to_vec)).collect();
+                let Some(files) = files else {
+                    return Decode::Needs(paths);
+                };
+                let load = Load { name: load.name.clone(), path: load.path.clone(), data: load.data.clone() };
+                let name = load.name.clone();
+                Decode::Done(decode(load, files).and_then(|p| finish(&name, p)))
+            })
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // hand every file read so far to the decoder before looking for results
+            while let Some(load) = self.next_load() {
+                let (load, reload) = match load {
+                    Ok(load) => load,
+                    Err(e) => return Some(e),
+                };
+                let paths = match needs(&load) {
+                    Ok(paths) => paths,
+                    Err(error) => return self.complete(load.name, load.path, reload, Err(error)),
+                };
+                if let Some(error) = self.request_dependencies(&load.path, paths.clone(), reload) {
+                    return self.complete(load.name, load.path, reload, Err(error));
+                }
+                let dependencies = Dependencies { files: &self.dependencies };
+                let files: Option<Vec<_>> = paths.iter().map(|path| dependencies.get(path).map(<[u8]>::to_vec)).collect();
+                let Some(files) = files else {
+                    self.waiting.push((load, reload));
+                    continue;
+                };
+                let decode = decode.clone();
+                self.decoder.spawn(move || {
+                    let (name, path) = (load.name.clone(), load.path.clone());
+                    // nothing touched by a panicking decoder is used afterwards
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || decode(load, files)))
+                        .unwrap_or_else(|_| Err(AssetError::Decode("decoder panicked".to_string())))
+                        .map(|p| Box::new(p) as Box<dyn Any + Send>);
+                    Decoded { name, path, reload, result }
+                })
+            }
+
+            let Decoded { name, path, reload, result } = loop {
+                let decoded = self.decoder.try_recv()?;
+                if !decoded.reload && self.cancelled.remove(&decoded.name) {
+                    continue;
+                }
+                break decoded;
+            };
+            let result = result.and_then(|p| match p.downcast::<P>() {
+                Ok(p) => finish(&name, *p),
+                Err(_) => Err(AssetError::Decode("decoded to an unexpected type".to_string())),
+            });
+            self.complete(name, path, reload, result)
+        }
     }
 }
 
-impl<T> Default for TypedAssets<T> {
+impl<T : 'static> Default for TypedAssets<T> {
     fn default() -> Self {
         #[cfg(target_arch = "wasm32")]
         type L = WebLoader;
         #[cfg(not(target_arch = "wasm32"))]
         type L = DiskLoader;
-        let loader = Box::new(L::default());
-        Self {
-            loaded: Default::default(),
-            pending: Default::default(),
-            loader,
-        }
+        Self::new(L::default())
     }
 }
 
+/// An `AssetSource` ready to create loaders for each asset type.
+enum SourceLoader {
+    Files,
+    Factory(Box<dyn Fn() -> Box<dyn Loader>>),
+}
+
 #[derive(Default)]
 pub struct GAssets {
     assets: HashMap<TypeId, Box<dyn AssetLoader>>,
-    total: usize,
-    pending: usize,
+    failed: HashMap<(TypeId, String), AssetFailure>,
+    reloaded: Vec<String>,
+    hot_reload: bool,
+    workers: Option<usize>,
+    sources: Vec<SourceLoader>,
+    /// assets by group, see `begin_group`
+    groups: HashMap<String, HashSet<(TypeId, String)>>,
+    group: Option<String>,
+    /// assets which were unloaded, kept to report handles still held to them
+    unloaded: Vec<(&'static str, String, Weak<dyn Any>)>,
+    sizes: HashMap<TypeId, AssetSize>,
+    /// changes since the last `drain_events`
+    events: Vec<AssetNotice>,
 }
 
 pub struct PollContext<'a> {
-    pub egui_ctx:&'a egui::Context
+    pub egui_ctx:&'a egui::Context,
+    pub rhai_engine:&'a rhai::Engine,
+    /// `None` when running headless
+    pub gl:Option<&'a Arc<eframe::glow::Context>>,
+}
+
+/// Type erased access to the assets of a `TypedAssets`, implemented for all of them.
+pub trait AssetStore {
+    fn type_name(&self) -> &'static str;
+    /// removes a loaded asset, returning a weak handle to see whether it is still held
+    fn unload_any(&mut self, name: &str) -> Option<Weak<dyn Any>>;
+    fn cancel_any(&mut self, name: &str) -> bool;
+    fn clear_failed_any(&mut self, name: &str) -> bool;
+    fn status_any(&self, name: &str) -> Option<AssetStatus>;
+    /// the number of assets with `status`
+    fn count_any(&self, status: AssetStatus) -> usize;
+    /// names of loaded assets with the number of handles held outside of the store
+    fn handles(&self) -> Vec<(String, usize)>;
+    /// the number of loaded assets and their summed size as given by `size`
+    fn memory(&self, size: &dyn Fn(&dyn Any) -> usize) -> (usize, usize);
 }
 
-pub trait AssetLoader {
-    fn poll(&mut self, g:&mut PollContext) -> bool;
+impl<T: 'static> AssetStore for TypedAssets<T> {
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn unload_any(&mut self, name: &str) -> Option<Weak<dyn Any>> {
+        let asset: Rc<dyn Any> = self.unload(name)?;
+        Some(Rc::downgrade(&asset))
+    }
+
+    fn cancel_any(&mut self, name: &str) -> bool {
+        self.cancel(name)
+    }
+
+    fn clear_failed_any(&mut self, name: &str) -> bool {
+        self.clear_failed(name)
+    }
+
+    fn status_any(&self, name: &str) -> Option<AssetStatus> {
+        self.status(name)
+    }
+
+    fn count_any(&self, status: AssetStatus) -> usize {
+        self.count(status)
+    }
+
+    fn handles(&self) -> Vec<(String, usize)> {
+        self.loaded
+            .iter()
+            .map(|(name, asset)| (name.clone(), Rc::strong_count(asset) - 1))
+            .collect()
+    }
+
+    fn memory(&self, size: &dyn Fn(&dyn Any) -> usize) -> (usize, usize) {
+        let bytes = self.loaded.values().map(|asset| size(&asset.data)).sum();
+        (self.loaded.len(), bytes)
+    }
+}
+
+/// Handles held to an asset, as reported by `GAssets::handles`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetHandles {
+    pub type_name: &'static str,
+    pub name: String,
+    /// handles held outside of `GAssets`
+    pub handles: usize,
+    /// the asset was unloaded but is kept alive by the handles
+    pub unloaded: bool,
+}
+
+/// Memory used by the loaded assets of one type, as reported by `GAssets::memory`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetMemory {
+    pub type_name: &'static str,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+/// Returns the size in bytes of an asset, given as `&dyn Any` holding the asset data.
+type AssetSize = Rc<dyn Fn(&dyn Any) -> usize>;
+
+pub trait AssetLoader: AssetStore {
+    fn poll(&mut self, g:&mut PollContext) -> Option<AssetChange>;
+    /// assets created along with the ones polled, e.g. the tileset atlases of a map
+    fn take_dependents(&mut self) -> Vec<Dependent> {
+        Vec::new()
+    }
     fn to_any_mut(&mut self) -> &mut dyn std::any::Any;
     fn to_any_ref(&self) -> &dyn std::any::Any;
+    /// whether files of the type can be decoded, false for types which were only inserted
+    fn can_decode(&self) -> bool {
+        true
+    }
+    /// the decoder given to `GAssets::register_decoder`, `None` for the built in types
+    fn decoder_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        None
+    }
 }
 
 impl AssetLoader for TypedAssets<String> {
-    fn poll(&mut self, _:&mut PollContext) -> bool {
+    fn poll(&mut self, _:&mut PollContext) -> Option<AssetChange> {
         self.poll(|l| match from_utf8(&l.data) {
             Ok(ok) => Ok(ok.to_string()),
-            Err(_) => Err(()),
+            Err(err) => Err(AssetError::Decode(err.to_string())),
         })
-        .is_some()
+        .map(AssetChange::from)
     }
 
     fn to_any_mut(&mut self) -> &mut dyn Any {
@@ -156,35 +836,65 @@ impl AssetLoader for TypedAssets<String> {
     }
 }
 
-struct TiledMapReader {
-    pub data: Vec<u8>,
+/// How the frames of a decoded atlas image are found.
+enum AtlasLayout {
+    Grid(AtlasGrid),
+    Frames(Vec<AtlasFrame>, Vec<GGAnimation>),
 }
 
-impl tiled::ResourceReader for TiledMapReader {
-    type Resource = Cursor<Vec<u8>>;
-    type Error = std::io::Error;
+/// whether `path` is the sidecar metadata of an atlas rather than its image
+fn is_atlas_meta(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
 
-    fn read_from(
-        &mut self,
-        _: &std::path::Path,
-    ) -> std::result::Result<Self::Resource, Self::Error> {
-        Ok(Cursor::new(self.data.clone()))
+/// the size of the grid given by a file name ending in `_CxR`, e.g. `tiles_8x4.png`, or 1x1
+fn atlas_grid_from_path(path: &str) -> AtlasGrid {
+    let stem = Path::new(path).file_stem().unwrap_or_default().to_string_lossy();
+    let size = stem.rsplit('_').next().and_then(|last| {
+        let (c, r) = last.split_once('x')?;
+        Some((c.parse::<u32>().ok()?, r.parse::<u32>().ok()?))
+    });
+    match size {
+        Some((cols, rows)) if cols > 0 && rows > 0 => AtlasGrid::new(cols, rows),
+        _ => AtlasGrid::new(1, 1),
     }
 }
 
-impl AssetLoader for TypedAssets<tiled::Map> {
-    fn poll(&mut self, _:&mut PollContext) -> bool {
-        self.poll(|load|{
-            let data = load.data.clone();
-            let reader = TiledMapReader { data };
-            let mut loader = tiled::Loader::with_reader(reader);
-            let path = load.path.clone();
-            let map = loader.load_tmx_map(path);
-            match map {
-                Ok(map) => Ok(map),
-                Err(_) => Err(()),
+/// the path of the image of the atlas metadata in `load`
+fn atlas_image_path(load: &Load) -> Result<String, AssetError> {
+    let meta = AtlasMeta::from_json(&load.data)?;
+    let dir = Path::new(&load.path).parent().unwrap_or(Path::new(""));
+    Ok(tiled_map::normalize_path(&dir.join(meta.image)))
+}
+
+/// Loads images as atlases, either from an image whose file name gives the
+/// grid, e.g. `tiles_8x4.png`, or from sidecar JSON metadata, see `AtlasMeta`.
+impl AssetLoader for TypedAssets<GGAtlas> {
+    fn poll(&mut self, g:&mut PollContext) -> Option<AssetChange> {
+        self.poll_decoded_with_dependencies(|load| {
+            match is_atlas_meta(&load.path) {
+                true => Ok(vec![atlas_image_path(load)?]),
+                false => Ok(Vec::new()),
+            }
+        }, |load: Load, files: Vec<Vec<u8>>| {
+            let decode_image = |data: &[u8]| GGAtlas::decode_image(data).map_err(|err| AssetError::Decode(err.to_string()));
+            match files.as_slice() {
+                [image] => {
+                    let meta = AtlasMeta::from_json(&load.data)?;
+                    let image = decode_image(image)?;
+                    let frames = meta.all_frames(image.size);
+                    Ok((image, AtlasLayout::Frames(frames, meta.animations)))
+                }
+                _ => Ok((decode_image(&load.data)?, AtlasLayout::Grid(atlas_grid_from_path(&load.path)))),
             }
-        }).is_some()
+        }, |name, (image, layout)| {
+            Ok(match layout {
+                AtlasLayout::Grid(grid) => GGAtlas::from_grid(g.egui_ctx, name, image, grid),
+                AtlasLayout::Frames(frames, animations) => {
+                    GGAtlas::from_frames(g.egui_ctx, name, image, frames).with_animations(animations)
+                }
+            })
+        }).map(AssetChange::from)
     }
 
     fn to_any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -196,28 +906,19 @@ impl AssetLoader for TypedAssets<tiled::Map> {
     }
 }
 
-impl AssetLoader for TypedAssets<GGAtlas> {
-    fn poll(&mut self, g:&mut PollContext) -> bool {
-        self.poll(|load|{
-            let path = Path::new(&load.path);
-            let file_name = path.file_stem().unwrap_or_default();
-            let split = file_name.to_str().unwrap_or_default();
-            let split = split.split("_");
-            let last = split.last();
-            let mut cols = 1;
-            let mut rows = 1;
-    
-            if let Some(last) = last {
-                let mut split = last.split("x");
-                if let (Some(c), Some(r)) = (split.next(), split.next()) {
-                    if let (Ok(c), Ok(r)) = (u8::from_str_radix(c, 10), u8::from_str_radix(r, 10)) {
-                        cols = c;
-                        rows = r;
-                    }
+impl AssetLoader for TypedAssets<rhai::AST> {
+    fn poll(&mut self, g:&mut PollContext) -> Option<AssetChange> {
+        self.poll(|load| {
+            let script = from_utf8(&load.data).map_err(|err| AssetError::Decode(err.to_string()))?;
+            match g.rhai_engine.compile(script) {
+                Ok(mut ast) => {
+                    ast.set_source(load.path);
+                    Ok(ast)
                 }
+                Err(err) => Err(AssetError::Decode(err.to_string())),
             }
-            Ok(GGAtlas::new(g.egui_ctx, load.name, &load.data, cols, rows))
-        }).is_some()
+        })
+        .map(AssetChange::from)
     }
 
     fn to_any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -230,17 +931,17 @@ impl AssetLoader for TypedAssets<GGAtlas> {
 }
 
 impl AssetLoader for TypedAssets<StaticSoundData> {
-    fn poll(&mut self, _:&mut PollContext) -> bool {
-        self.poll(|load| {
+    fn poll(&mut self, _:&mut PollContext) -> Option<AssetChange> {
+        self.poll_decoded(|load| {
             let data = load.data.clone();
             let cursor = std::io::Cursor::new(data);
             let cursor = StaticSoundData::from_cursor(cursor);
             match cursor {
                 Ok(sound_data) => Ok(sound_data),
-                Err(_) => Err(()),
+                Err(err) => Err(AssetError::Decode(err.to_string())),
             }
-        })
-        .is_some()
+        }, |_, sound_data| Ok(sound_data))
+        .map(AssetChange::from)
     }
 
     fn to_any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -252,57 +953,810 @@ impl AssetLoader for TypedAssets<StaticSoundData> {
     }
 }
 
+type DecodeFn<T> = fn(Load, &mut PollContext) -> Result<T, AssetError>;
+
+/// Creates the `TypedAssets` of a built in asset type.
+type NewAssets = fn(&GAssets) -> Box<dyn AssetLoader>;
+
+/// The assets of a type loaded with a decoder given to `GAssets::register_decoder`.
+///
+/// Without a decoder the assets can only be inserted.
+struct DecoderAssets<T> {
+    assets: TypedAssets<T>,
+    decode: Option<DecodeFn<T>>,
+}
+
+impl<T: 'static> AssetStore for DecoderAssets<T> {
+    fn type_name(&self) -> &'static str {
+        self.assets.type_name()
+    }
+
+    fn unload_any(&mut self, name: &str) -> Option<Weak<dyn Any>> {
+        self.assets.unload_any(name)
+    }
+
+    fn cancel_any(&mut self, name: &str) -> bool {
+        self.assets.cancel_any(name)
+    }
+
+    fn clear_failed_any(&mut self, name: &str) -> bool {
+        self.assets.clear_failed_any(name)
+    }
+
+    fn status_any(&self, name: &str) -> Option<AssetStatus> {
+        self.assets.status_any(name)
+    }
+
+    fn count_any(&self, status: AssetStatus) -> usize {
+        self.assets.count_any(status)
+    }
+
+    fn handles(&self) -> Vec<(String, usize)> {
+        self.assets.handles()
+    }
+
+    fn memory(&self, size: &dyn Fn(&dyn Any) -> usize) -> (usize, usize) {
+        self.assets.memory(size)
+    }
+}
+
+impl<T: 'static> AssetLoader for DecoderAssets<T> {
+    fn poll(&mut self, g:&mut PollContext) -> Option<AssetChange> {
+        let decode = self.decode;
+        let type_name = self.assets.type_name();
+        self.assets
+            .poll(|load| match decode {
+                Some(decode) => decode(load, g),
+                None => Err(AssetError::NoDecoder(type_name.to_string())),
+            })
+            .map(AssetChange::from)
+    }
+
+    /// the `TypedAssets` holding the assets, not the decoder
+    fn to_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.assets
+    }
+
+    fn to_any_ref(&self) -> &dyn Any {
+        &self.assets
+    }
+
+    fn can_decode(&self) -> bool {
+        self.decode.is_some()
+    }
+
+    fn decoder_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(&mut self.decode)
+    }
+}
+
 impl GAssets {
-    pub fn load<T: 'static + Clone>(&mut self, path: &str, name: &str)
-    where
-        TypedAssets<T>: AssetLoader,
-    {
+    /// when `hot_reload` is set, assets are reloaded when their files change on disk
+    ///
+    /// has no effect on the web
+    pub fn with_hot_reload(mut self, hot_reload: bool) -> Self {
+        self.hot_reload = hot_reload;
+        self
+    }
+
+    /// sets the number of files read and decoded in parallel per asset type
+    ///
+    /// has no effect on the web
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// sets where files are read from, trying each source in order
+    ///
+    /// without any sources files are read with `AssetSource::Files`
+    pub fn with_sources(mut self, sources: Vec<AssetSource>) -> Self {
+        self.sources = sources
+            .into_iter()
+            .map(|source| match source {
+                AssetSource::Files => SourceLoader::Files,
+                AssetSource::Pack(path) => {
+                    let pack = PackLoader::new(&path);
+                    SourceLoader::Factory(Box::new(move || Box::new(pack.clone())))
+                }
+                AssetSource::Embedded(embedded) => {
+                    SourceLoader::Factory(Box::new(move || Box::new(embedded.clone())))
+                }
+                AssetSource::Custom(factory) => SourceLoader::Factory(Box::new(move || factory())),
+            })
+            .collect();
+        self
+    }
+
+    /// reads files with loaders created by `factory`, once per asset type
+    pub fn with_loader<L: Loader>(self, factory: impl Fn() -> L + Send + Sync + 'static) -> Self {
+        self.with_sources(vec![AssetSource::custom(factory)])
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn files_loader(&self) -> Box<dyn Loader> {
+        let loader = DiskLoader::default().with_watch(self.hot_reload);
+        match self.workers {
+            Some(workers) => Box::new(loader.with_workers(workers)),
+            None => Box::new(loader),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn files_loader(&self) -> Box<dyn Loader> {
+        Box::new(WebLoader::default())
+    }
+
+    fn new_typed_assets<T: 'static>(&self) -> TypedAssets<T> {
+        let mut loaders: Vec<Box<dyn Loader>> = self
+            .sources
+            .iter()
+            .map(|source| match source {
+                SourceLoader::Files => self.files_loader(),
+                SourceLoader::Factory(factory) => factory(),
+            })
+            .collect();
+        let assets = match loaders.len() {
+            0 => TypedAssets::new(self.files_loader()),
+            1 => TypedAssets::new(loaders.remove(0)),
+            _ => TypedAssets::new(ChainLoader::new(loaders)),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(workers) = self.workers {
+            return assets.with_decode_workers(workers);
+        }
+        assets
+    }
+
+    /// returns how to create the assets of one of the types supported without `register_decoder`
+    fn builtin_assets<T: 'static>() -> Option<NewAssets> {
+        fn new<T: 'static>(assets: &GAssets) -> Box<dyn AssetLoader>
+        where
+            TypedAssets<T>: AssetLoader,
+        {
+            Box::new(assets.new_typed_assets::<T>())
+        }
         let type_id = TypeId::of::<T>();
-        let assets = match self.assets.get_mut(&type_id) {
-            Some(assets) => assets,
-            None => {
-                let assets: TypedAssets<T> = TypedAssets::default();
-                self.assets.insert(type_id, Box::new(assets));
-                self.assets.get_mut(&type_id).unwrap()
+        let new: NewAssets = if type_id == TypeId::of::<String>() {
+            new::<String>
+        } else if type_id == TypeId::of::<GGAtlas>() {
+            new::<GGAtlas>
+        } else if type_id == TypeId::of::<StaticSoundData>() {
+            new::<StaticSoundData>
+        } else if type_id == TypeId::of::<tiled::Map>() {
+            new::<tiled::Map>
+        } else if type_id == TypeId::of::<rhai::AST>() {
+            new::<rhai::AST>
+        } else if type_id == TypeId::of::<GGShader>() {
+            new::<GGShader>
+        } else {
+            return None;
+        };
+        Some(new)
+    }
+
+    /// loads assets of type `T` by decoding their files with `decode`
+    ///
+    /// A decoder registered before is replaced, files already read are decoded with the new one.
+    /// Decoders of the types supported out of the box may be replaced as well, but only
+    /// before any asset of the type is loaded, `AssetError::DecoderInUse` is returned otherwise.
+    pub fn register_decoder<T: 'static>(&mut self, decode: DecodeFn<T>) -> Result<(), AssetError> {
+        let type_id = TypeId::of::<T>();
+        if let Some(assets) = self.assets.get_mut(&type_id) {
+            let Some(decoder) = assets.decoder_mut() else {
+                return Err(AssetError::DecoderInUse(std::any::type_name::<T>().to_string()));
+            };
+            *decoder.downcast_mut::<Option<DecodeFn<T>>>().expect("decoders are stored by their type id") = Some(decode);
+            return Ok(());
+        }
+        let assets = DecoderAssets {
+            assets: self.new_typed_assets::<T>(),
+            decode: Some(decode),
+        };
+        self.assets.insert(type_id, Box::new(assets));
+        Ok(())
+    }
+
+    /// whether files of type `T` can be loaded, either by a built in or a registered decoder
+    pub fn can_decode<T: 'static>(&self) -> bool {
+        match self.assets.get(&TypeId::of::<T>()) {
+            Some(assets) => assets.can_decode(),
+            None => Self::builtin_assets::<T>().is_some(),
+        }
+    }
+
+    fn typed_assets_mut<T: 'static>(&mut self) -> &mut TypedAssets<T> {
+        let type_id = TypeId::of::<T>();
+        if !self.assets.contains_key(&type_id) {
+            // without a decoder the assets can still be inserted
+            let assets = match Self::builtin_assets::<T>() {
+                Some(new) => new(self),
+                None => Box::new(DecoderAssets {
+                    assets: self.new_typed_assets::<T>(),
+                    decode: None,
+                }),
+            };
+            self.assets.insert(type_id, assets);
+        }
+        let assets: &mut dyn Any = self.assets.get_mut(&type_id).unwrap().to_any_mut();
+        assets.downcast_mut().expect("assets are stored by their type id")
+    }
+
+    /// requests the file at `path` and loads it as `name`, returning a handle to the asset
+    ///
+    /// the handle fails with `AssetError::NoDecoder` when files of type `T` can not be decoded
+    pub fn load<T: 'static + Clone>(&mut self, path: &str, name: &str) -> Handle<T> {
+        let can_decode = self.can_decode::<T>();
+        let assets = self.typed_assets_mut::<T>();
+        let handle = match can_decode {
+            true => assets.load(path, name),
+            false => assets.fail(path, name, AssetError::NoDecoder(std::any::type_name::<T>().to_string())),
+        };
+        if let Some(group) = self.group.clone() {
+            self.tag::<T>(name, &group);
+        }
+        handle
+    }
+
+    /// adds an asset which was not loaded from a file, replacing any with the same name
+    pub fn insert<T: 'static + Clone>(&mut self, name: &str, path: &str, data: T) -> Rc<GGAsset<T>> {
+        let asset = self.typed_assets_mut::<T>().insert(name, path, data);
+        if let Some(group) = self.group.clone() {
+            self.tag::<T>(name, &group);
+        }
+        asset
+    }
+
+    /// adds `data` as a dependent of the asset of type `O` named `owner`, in the same groups as it
+    fn insert_dependent<O: 'static, T: 'static + Clone>(&mut self, owner: &str, name: &str, path: &str, data: T) {
+        self.insert(name, path, data);
+        let owner = (TypeId::of::<O>(), owner.to_string());
+        for group in self.groups.values_mut() {
+            if group.contains(&owner) {
+                group.insert((TypeId::of::<T>(), name.to_string()));
             }
+        }
+    }
+
+    /// adds loads made until `end_group` to `group`, for unloading them together with `unload_group`
+    pub fn begin_group(&mut self, group: &str) {
+        self.group = Some(group.to_string());
+    }
+
+    pub fn end_group(&mut self) {
+        self.group = None;
+    }
+
+    /// adds the asset of type `T` with the given name to `group`
+    pub fn tag<T: 'static>(&mut self, name: &str, group: &str) {
+        self.groups
+            .entry(group.to_string())
+            .or_default()
+            .insert((TypeId::of::<T>(), name.to_string()));
+    }
+
+    /// unloads the asset of type `T` with the given name, or cancels its load
+    ///
+    /// returns false if there was no such asset
+    pub fn unload<T: 'static>(&mut self, name: &str) -> bool {
+        self.unload_any(TypeId::of::<T>(), name)
+    }
+
+    fn unload_any(&mut self, type_id: TypeId, name: &str) -> bool {
+        let Some(assets) = self.assets.get_mut(&type_id) else {
+            return false;
         };
-        let assets: &mut dyn Any = assets.to_any_mut();
-        let Some(assets) = assets.downcast_mut::<TypedAssets<T>>() else {
-            return;
+        for group in self.groups.values_mut() {
+            group.remove(&(type_id, name.to_string()));
+        }
+        let cancelled = assets.cancel_any(name);
+        let failed = assets.clear_failed_any(name);
+        let unloaded = assets.unload_any(name);
+        if failed || unloaded.is_some() {
+            self.failed.remove(&(type_id, name.to_string()));
+        }
+        if let Some(asset) = &unloaded {
+            self.unloaded.push((assets.type_name(), name.to_string(), asset.clone()));
+        }
+        cancelled || failed || unloaded.is_some()
+    }
+
+    /// unloads all assets in `group`, returning how many were unloaded
+    pub fn unload_group(&mut self, group: &str) -> usize {
+        let Some(assets) = self.groups.remove(group) else {
+            return 0;
         };
-        assets.load(path, name);
-        self.pending += 1;
-        self.total += 1;
+        assets
+            .into_iter()
+            .filter(|(type_id, name)| self.unload_any(*type_id, name))
+            .count()
+    }
+
+    /// reports the handles held to assets outside of `GAssets`, including
+    /// unloaded assets which are kept alive by them
+    pub fn handles(&mut self) -> Vec<AssetHandles> {
+        self.unloaded.retain(|(_, _, asset)| asset.strong_count() > 0);
+        let mut handles: Vec<_> = self
+            .assets
+            .values()
+            .flat_map(|assets| {
+                let type_name = assets.type_name();
+                assets.handles().into_iter().map(move |(name, handles)| AssetHandles {
+                    type_name,
+                    name,
+                    handles,
+                    unloaded: false,
+                })
+            })
+            .chain(self.unloaded.iter().map(|(type_name, name, asset)| AssetHandles {
+                type_name,
+                name: name.clone(),
+                handles: asset.strong_count(),
+                unloaded: true,
+            }))
+            .collect();
+        handles.sort_by(|a, b| (a.type_name, &a.name).cmp(&(b.type_name, &b.name)));
+        handles
+    }
+
+    /// accounts the memory of assets of type `T` with `size`, in bytes
+    ///
+    /// `GGAtlas` and `StaticSoundData` are accounted for by default
+    pub fn track_memory<T: 'static>(&mut self, size: fn(&T) -> usize) {
+        let size = move |data: &dyn Any| data.downcast_ref::<T>().map(size).unwrap_or_default();
+        self.sizes.insert(TypeId::of::<T>(), Rc::new(size));
+    }
+
+    fn size_of(&self, type_id: TypeId) -> Option<AssetSize> {
+        if let Some(size) = self.sizes.get(&type_id) {
+            return Some(size.clone());
+        }
+        if type_id == TypeId::of::<GGAtlas>() {
+            return Some(Rc::new(|data: &dyn Any| {
+                data.downcast_ref::<GGAtlas>().map(GGAtlas::memory_size).unwrap_or_default()
+            }));
+        }
+        if type_id == TypeId::of::<StaticSoundData>() {
+            return Some(Rc::new(|data: &dyn Any| {
+                data.downcast_ref::<StaticSoundData>()
+                    .map(|sound| std::mem::size_of_val(&*sound.frames))
+                    .unwrap_or_default()
+            }));
+        }
+        None
+    }
+
+    /// reports the memory used by loaded assets of each type with memory accounting
+    pub fn memory(&self) -> Vec<AssetMemory> {
+        let mut memory: Vec<_> = self
+            .assets
+            .iter()
+            .filter_map(|(type_id, assets)| {
+                let size = self.size_of(*type_id)?;
+                let (count, bytes) = assets.memory(&*size);
+                Some(AssetMemory {
+                    type_name: assets.type_name(),
+                    count,
+                    bytes,
+                })
+            })
+            .collect();
+        memory.sort_by_key(|m| m.type_name);
+        memory
     }
 
     pub fn get<T: 'static + Clone>(&self, name: &str) -> Option<Rc<GGAsset<T>>> {
-        let assets = self.assets.get(&TypeId::of::<T>());
-        let Some(assets) = assets else {
-            return None;
-        };
+        let assets = self.assets.get(&TypeId::of::<T>())?;
         let assets: &dyn Any = assets.to_any_ref();
-        let assets: Option<&TypedAssets<T>> = assets.downcast_ref();
-        let Some(assets) = assets else { return None };
+        let assets: &TypedAssets<T> = assets.downcast_ref()?;
         assets.get(name)
     }
 
+    /// returns all loaded assets of type `T`, ordered by name
+    pub fn get_all<T: 'static + Clone>(&self) -> Vec<Rc<GGAsset<T>>> {
+        let Some(assets) = self.assets.get(&TypeId::of::<T>()) else {
+            return Vec::new();
+        };
+        let assets: &dyn Any = assets.to_any_ref();
+        let Some(assets) = assets.downcast_ref::<TypedAssets<T>>() else {
+            return Vec::new();
+        };
+        let mut all: Vec<_> = assets.iter().cloned().collect();
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        all
+    }
+
     pub fn poll(&mut self, mut g:PollContext) {
-        for assets in self.assets.values_mut() {
-            if assets.poll(&mut g) {
-                self.pending -= 1;
+        for (type_id, assets) in self.assets.iter_mut() {
+            while let Some(change) = assets.poll(&mut g) {
+                match &change {
+                    AssetChange::Loaded(name) => {
+                        self.failed.remove(&(*type_id, name.clone()));
+                    }
+                    AssetChange::LoadFailed(failure) => {
+                        tracing::warn!("failed to load {} from {}: {}", failure.name, failure.path, failure.error);
+                        self.failed.insert((*type_id, failure.name.clone()), failure.clone());
+                    }
+                    AssetChange::Reloaded(name) => {
+                        tracing::info!("reloaded {name}");
+                        self.failed.remove(&(*type_id, name.clone()));
+                        self.reloaded.push(name.clone());
+                    }
+                    AssetChange::ReloadFailed(failure) => {
+                        tracing::warn!("failed to reload {} from {}: {}", failure.name, failure.path, failure.error);
+                        self.failed.insert((*type_id, failure.name.clone()), failure.clone());
+                    }
+                    AssetChange::Progress { .. } => {}
+                }
+                self.events.push(AssetNotice {
+                    type_id: *type_id,
+                    type_name: assets.type_name(),
+                    change,
+                });
             }
         }
+        if self.events.len() > MAX_ASSET_EVENTS {
+            self.events.drain(..self.events.len() - MAX_ASSET_EVENTS);
+        }
+        let dependents: Vec<_> = self.assets.values_mut().flat_map(|assets| assets.take_dependents()).collect();
+        for dependent in dependents {
+            dependent(self);
+        }
+    }
+
+    /// returns why the asset of type `T` with the given name failed to load, if it did
+    pub fn error<T: 'static>(&self, name: &str) -> Option<&AssetError> {
+        self.failed.get(&(TypeId::of::<T>(), name.to_string())).map(|f| &f.error)
+    }
+
+    /// returns all assets which failed to load
+    pub fn failed(&self) -> impl Iterator<Item = &AssetFailure> {
+        self.failed.values()
+    }
+
+    /// returns the names of assets reloaded since the last call
+    pub fn take_reloaded(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.reloaded)
     }
 
+    /// returns what happened to assets since the last call, in the order it happened
+    ///
+    /// Only the last `MAX_ASSET_EVENTS` are kept when events are not drained.
+    pub fn drain_events(&mut self) -> Vec<AssetNotice> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn status<T: 'static>(&self, name: &str) -> Option<AssetStatus> {
+        self.assets.get(&TypeId::of::<T>())?.status_any(name)
+    }
+
+    /// returns the number of assets of all types with `status`
+    pub fn count(&self, status: AssetStatus) -> usize {
+        self.assets
+            .values()
+            .map(|assets| assets.count_any(status))
+            .sum()
+    }
+
+    /// returns the number of assets which are queued or loading
     pub fn pending(&self) -> usize {
-        self.pending
+        self.count(AssetStatus::Queued) + self.count(AssetStatus::Loading)
     }
 
+    /// returns the number of assets which were requested and not unloaded since
     pub fn total(&self) -> usize {
-        self.total
+        self.pending() + self.loaded()
     }
 
+    /// returns the number of assets which are done loading, including the ones that failed
     pub fn loaded(&self) -> usize {
-        self.total - self.pending
+        self.count(AssetStatus::Loaded) + self.count(AssetStatus::Failed)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use eframe::egui;
+
+    use crate::{
+        headless::fixture::{hot_reload, touch, EmptyApp},
+        AssetChange, AssetError, AssetPackBuilder, Load, PollContext, AssetSource, AssetStatus, GGApp, GGAtlas, GGEngine,
+        GGHeadless, GGRunOptions, InitContext, UpdateContext,
+    };
+
+    struct FailingApp;
+
+    impl GGApp for FailingApp {
+        fn init(&mut self, g: InitContext) {
+            g.assets.load::<String>("missing.txt", "missing");
+            g.assets.load::<GGAtlas>("Cargo.toml", "not_an_image");
+            // the same name for another type does not clear the failure
+            g.assets.load::<String>("Cargo.toml", "not_an_image");
+        }
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    #[test]
+    fn test_asset_errors() {
+        let mut headless = GGEngine::headless(FailingApp, Default::default());
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+
+        let assets = headless.assets();
+        assert_eq!(assets.error::<String>("missing"), Some(&AssetError::NotFound));
+        assert_eq!(assets.error::<GGAtlas>("missing"), None);
+        assert!(matches!(assets.error::<GGAtlas>("not_an_image"), Some(AssetError::Decode(_))));
+        assert_eq!(assets.error::<String>("not_an_image"), None);
+        assert_eq!(assets.failed().count(), 2);
+        assert!(assets.get::<GGAtlas>("not_an_image").is_none());
+    }
+
+    struct TextApp {
+        path: String,
+    }
+
+    impl GGApp for TextApp {
+        fn init(&mut self, g: InitContext) {
+            g.assets.load::<String>(&self.path, "text");
+        }
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let path = std::env::temp_dir().join(format!("ggsdk_reload_{}.txt", std::process::id()));
+        std::fs::write(&path, "before").unwrap();
+
+        let file = path.to_string_lossy().to_string();
+        let mut headless = hot_reload(move |assets| {
+            assets.load::<String>(&file, "text");
+        });
+        let before = headless.assets().get::<String>("text").unwrap();
+        assert_eq!(before.data, "before");
+
+        std::fs::write(&path, "after").unwrap();
+        touch(&path);
+        let reloaded = |h: &mut GGHeadless<_>| h.assets().get::<String>("text").unwrap().data == "after";
+        assert!(headless.step_until(reloaded, Duration::from_secs(10)), "asset was not reloaded");
+        assert_eq!(headless.assets().take_reloaded(), vec!["text".to_string()]);
+        assert!(headless.assets().take_reloaded().is_empty());
+        assert_eq!(headless.assets().pending(), 0);
+        assert_eq!(before.data, "before");
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_asset_pack_loading() {
+        let path = std::env::temp_dir().join(format!("ggsdk_pack_{}.ggpack", std::process::id()));
+        AssetPackBuilder::new()
+            .add("assets/text.txt", b"packed".to_vec())
+            .write(&path)
+            .unwrap();
+
+        let app = TextApp {
+            path: "assets/text.txt".to_string(),
+        };
+        let options = GGRunOptions {
+            asset_sources: vec![AssetSource::Pack(path.to_string_lossy().to_string())],
+            ..Default::default()
+        };
+        let mut headless = GGEngine::headless(app, options);
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        assert_eq!(headless.assets().get::<String>("text").unwrap().data, "packed");
+
+        // files missing from the pack fall back to disk
+        let app = TextApp {
+            path: "Cargo.toml".to_string(),
+        };
+        let options = GGRunOptions {
+            asset_sources: vec![AssetSource::Pack(path.to_string_lossy().to_string()), AssetSource::Files],
+            ..Default::default()
+        };
+        let mut headless = GGEngine::headless(app, options);
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        assert!(headless.assets().get::<String>("text").unwrap().data.contains("ggsdk_internal"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    struct LevelApp;
+
+    impl GGApp for LevelApp {
+        fn init(&mut self, g: InitContext) {
+            g.assets.load::<String>("Cargo.toml", "manifest");
+            g.assets.begin_group("level");
+            g.assets.load::<String>("src/lib.rs", "lib");
+            g.assets.load::<GGAtlas>("../examples/treasure_hunter/assets/basic_32x32.png", "basic");
+            g.assets.end_group();
+        }
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    #[test]
+    fn test_unload() {
+        let mut headless = GGEngine::headless(LevelApp, Default::default());
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        let mut assets = headless.assets();
+        assert_eq!(assets.total(), 3);
+
+        let memory = assets.memory();
+        assert_eq!(memory.len(), 1);
+        assert_eq!(memory[0].type_name, std::any::type_name::<GGAtlas>());
+        assert_eq!(memory[0].count, 1);
+        let atlas = assets.get::<GGAtlas>("basic").unwrap();
+        let [w, h] = atlas.texture.size();
+        assert_eq!(memory[0].bytes, w * h * 4);
+
+        let lib = assets.get::<String>("lib").unwrap();
+        assert!(assets.handles().iter().any(|h| h.name == "lib" && h.handles == 1 && !h.unloaded));
+
+        assert_eq!(assets.unload_group("level"), 2);
+        assert_eq!(assets.unload_group("level"), 0);
+        assert!(assets.get::<String>("lib").is_none());
+        assert!(assets.get::<String>("manifest").is_some());
+        assert_eq!(assets.total(), 1);
+        assert_eq!(assets.memory()[0].count, 0);
+
+        drop(atlas);
+        let handles = assets.handles();
+        assert_eq!(handles.len(), 2);
+        assert!(handles.iter().any(|h| h.name == "lib" && h.handles == 1 && h.unloaded));
+        drop(lib);
+        assert_eq!(assets.handles().len(), 1);
+
+        assert!(assets.unload::<String>("manifest"));
+        assert!(!assets.unload::<String>("manifest"));
+        assert_eq!(assets.total(), 0);
+
+        // unloading a pending asset cancels it
+        assets.load::<String>("Cargo.toml", "manifest");
+        assert!(assets.unload::<String>("manifest"));
+        assert_eq!(assets.pending(), 0);
+        drop(assets);
+        headless.step_n(10);
+        assert!(headless.assets().get::<String>("manifest").is_none());
+    }
+
+    #[test]
+    fn test_asset_status() {
+        let mut headless = GGEngine::headless(EmptyApp, Default::default());
+        {
+            let mut assets = headless.assets();
+            assets.load::<String>("Cargo.toml", "manifest");
+            assets.load::<String>("Cargo.toml", "manifest");
+            assets.load::<String>("Cargo.toml", "copy");
+            assets.load::<String>("missing.txt", "missing");
+            assert_eq!(assets.pending(), 3);
+            assert_eq!(assets.total(), 3);
+            assert_eq!(assets.status::<String>("manifest"), Some(AssetStatus::Queued));
+            assert_eq!(assets.status::<String>("other"), None);
+        }
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+
+        let mut assets = headless.assets();
+        assert_eq!((assets.pending(), assets.loaded(), assets.total()), (0, 3, 3));
+        assert_eq!((assets.count(AssetStatus::Loaded), assets.count(AssetStatus::Failed)), (2, 1));
+        assert_eq!(assets.status::<String>("manifest"), Some(AssetStatus::Loaded));
+        assert_eq!(assets.status::<String>("copy"), Some(AssetStatus::Loaded));
+        assert_eq!(assets.status::<String>("missing"), Some(AssetStatus::Failed));
+        assert_eq!(assets.get::<String>("manifest").unwrap().data, assets.get::<String>("copy").unwrap().data);
+
+        let mut changes: Vec<_> = assets
+            .drain_events()
+            .into_iter()
+            .filter(|e| e.type_name == std::any::type_name::<String>())
+            .map(|e| match e.change {
+                AssetChange::Loaded(name) => name,
+                AssetChange::LoadFailed(failure) => format!("{} failed", failure.name),
+                change => panic!("unexpected change {change:?}"),
+            })
+            .collect();
+        changes.sort();
+        assert_eq!(changes, vec!["copy", "manifest", "missing failed"]);
+        assert!(assets.drain_events().is_empty());
+
+        // loading a loaded asset again does nothing
+        assets.load::<String>("Cargo.toml", "manifest");
+        assert_eq!(assets.pending(), 0);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Version(u32, u32, u32);
+
+    fn decode_version(load: Load, _: &mut PollContext) -> Result<Version, AssetError> {
+        let text = std::str::from_utf8(&load.data).map_err(|err| AssetError::Decode(err.to_string()))?;
+        let version = text
+            .lines()
+            .find_map(|line| line.strip_prefix("version = "))
+            .ok_or_else(|| AssetError::Decode("no version".to_string()))?;
+        let parts: Vec<u32> = version
+            .trim_matches('"')
+            .split('.')
+            .map(|part| part.parse().map_err(|_| AssetError::Decode(format!("bad version {version}"))))
+            .collect::<Result<_, _>>()?;
+        match parts.as_slice() {
+            [major, minor, patch] => Ok(Version(*major, *minor, *patch)),
+            _ => Err(AssetError::Decode(format!("bad version {version}"))),
+        }
+    }
+
+    #[test]
+    fn test_register_decoder() {
+        let path = std::env::temp_dir().join(format!("ggsdk_version_{}.toml", std::process::id()));
+        std::fs::write(&path, "[package]\nversion = \"1.2.3\"\n").unwrap();
+
+        let mut headless = GGEngine::headless(EmptyApp, Default::default());
+        let (version, broken, unknown) = {
+            let mut assets = headless.assets();
+            // types without a decoder can be inserted, but not loaded
+            assets.insert("inserted", "", Version(0, 0, 1));
+            let unknown = assets.load::<Version>(&path.to_string_lossy(), "unknown");
+            assert_eq!(unknown.error(), Some(AssetError::NoDecoder(std::any::type_name::<Version>().to_string())));
+
+            assert_eq!(assets.register_decoder(decode_version), Ok(()));
+            // registering again replaces the decoder
+            assert_eq!(assets.register_decoder(decode_version), Ok(()));
+            assets.load::<String>("Cargo.toml", "manifest");
+            assert!(matches!(
+                assets.register_decoder(|_, _| Ok(String::new())),
+                Err(AssetError::DecoderInUse(_))
+            ));
+            (
+                assets.load::<Version>(&path.to_string_lossy(), "version"),
+                assets.load::<Version>("src/lib.rs", "broken"),
+                unknown,
+            )
+        };
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+
+        assert_eq!(version.get().unwrap().data, Version(1, 2, 3));
+        assert!(matches!(broken.error(), Some(AssetError::Decode(_))));
+        assert!(matches!(headless.assets().error::<Version>("unknown"), Some(AssetError::NoDecoder(_))));
+        assert!(!unknown.is_loaded());
+        assert_eq!(headless.assets().get::<Version>("inserted").unwrap().data, Version(0, 0, 1));
+        assert_eq!(headless.assets().get::<Version>("version").unwrap().data, Version(1, 2, 3));
+        assert!(headless.assets().unload::<Version>("version"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_atlas_metadata() {
+        let dir = std::env::temp_dir().join(format!("ggsdk_atlas_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("meta")).unwrap();
+        std::fs::copy("../examples/treasure_hunter/assets/basic_32x32.png", dir.join("basic.png")).unwrap();
+        std::fs::write(
+            dir.join("meta/basic.json"),
+            r#"{ "image": "../basic.png", "frames": [{ "name": "hero", "rect": [8, 16, 8, 16], "pivot": [0.5, 1] }] }"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("meta/missing.json"), r#"{ "image": "missing.png" }"#).unwrap();
+
+        let mut headless = GGEngine::headless(EmptyApp, Default::default());
+        let (meta, grid, missing) = {
+            let mut assets = headless.assets();
+            let path = |file: &str| dir.join(file).to_string_lossy().to_string();
+            (
+                assets.load::<GGAtlas>(&path("meta/basic.json"), "meta"),
+                assets.load::<GGAtlas>("../examples/treasure_hunter/assets/basic_32x32.png", "grid"),
+                assets.load::<GGAtlas>(&path("meta/missing.json"), "missing"),
+            )
+        };
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+
+        let meta = meta.get().unwrap();
+        assert_eq!(meta.frames().len(), 1);
+        assert_eq!(meta.frame_index("hero"), Some(0));
+        let hero = meta.named_frame("hero").unwrap();
+        assert_eq!(meta.uv(hero), egui::Rect::from_min_max(egui::pos2(1.0 / 32.0, 2.0 / 32.0), egui::pos2(2.0 / 32.0, 4.0 / 32.0)));
+        let grid = grid.get().unwrap();
+        assert_eq!((grid.cols, grid.rows, grid.frames().len()), (32, 32, 1024));
+        assert!(matches!(missing.error(), Some(AssetError::Dependency { error, .. }) if *error == AssetError::NotFound));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs,
+    io,
+    ops::Range,
+    path::Path,
+    rc::Rc,
+};
+
+use crate::{AssetError, Loader, LoaderEvent};
+
+const MAGIC: &[u8; 4] = b"GGPK";
+const VERSION: u32 = 1;
+
+/// A bundle of files addressed by the paths they had when packed.
+///
+/// The layout is `GGPK`, a version, the number of files, an index of
+/// `(path, length)` pairs and then the contents of each file in index order.
+/// All integers are little endian.
+pub struct AssetPack {
+    entries: HashMap<String, Range<usize>>,
+    data: Vec<u8>,
+}
+
+/// paths are stored with `/` separators and without a leading `./`
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], AssetError> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+        let Some(end) = end else {
+            return Err(AssetError::Decode("pack is truncated".to_string()));
+        };
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, AssetError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, AssetError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+impl AssetPack {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, AssetError> {
+        let mut reader = Reader { data: &data, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(AssetError::Decode("not an asset pack".to_string()));
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(AssetError::Decode(format!("unsupported pack version {version}")));
+        }
+        let count = reader.u32()?;
+        let mut index = Vec::new();
+        for _ in 0..count {
+            let len = reader.u32()? as usize;
+            let path = std::str::from_utf8(reader.take(len)?)
+                .map_err(|err| AssetError::Decode(err.to_string()))?
+                .to_string();
+            let size = reader.u64()? as usize;
+            index.push((path, size));
+        }
+        let mut entries = HashMap::new();
+        for (path, size) in index {
+            let start = reader.pos;
+            reader.take(size)?;
+            entries.insert(path, start..reader.pos);
+        }
+        Ok(Self { entries, data })
+    }
+
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        let range = self.entries.get(&normalize(path))?;
+        Some(&self.data[range.clone()])
+    }
+
+    /// returns the paths of all files in the pack, in no particular order
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|path| path.as_str())
+    }
+}
+
+/// Collects files and writes them as an `AssetPack`.
+#[derive(Default)]
+pub struct AssetPackBuilder {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl AssetPackBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a file which is loaded with `path`
+    pub fn add(mut self, path: &str, data: Vec<u8>) -> Self {
+        self.files.insert(normalize(path), data);
+        self
+    }
+
+    /// adds every file below `dir`, each loaded with its path joined onto `dir`
+    ///
+    /// packing `assets` makes `assets/sfx/coin.mp3` loadable as such
+    pub fn add_dir(mut self, dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut dirs = vec![dir.as_ref().to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else {
+                    let data = fs::read(&path)?;
+                    self = self.add(&path.to_string_lossy(), data);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.files.len() as u32).to_le_bytes());
+        for (path, data) in self.files.iter() {
+            bytes.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(path.as_bytes());
+            bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        }
+        for data in self.files.values() {
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn build(&self) -> AssetPack {
+        AssetPack::from_bytes(self.to_bytes()).expect("builder always writes a valid pack")
+    }
+}
+
+/// The pack shared by every `PackLoader` cloned from the same loader.
+struct PackSource {
+    loader: Option<Box<dyn Loader>>,
+    pack: Option<Result<Rc<AssetPack>, AssetError>>,
+}
+
+impl PackSource {
+    fn poll(&mut self) -> Option<Result<Rc<AssetPack>, AssetError>> {
+        if self.pack.is_none()
+            && let Some(loader) = &mut self.loader
+        {
+            match loader.poll() {
+                Some(LoaderEvent::Load(path, data)) => {
+                    let pack = AssetPack::from_bytes(data);
+                    if let Err(err) = &pack {
+                        tracing::warn!("failed to read asset pack {path}: {err}");
+                    }
+                    self.pack = Some(pack.map(Rc::new));
+                }
+                Some(LoaderEvent::LoadFailed(path, err)) => {
+                    tracing::warn!("failed to load asset pack {path}: {err}");
+                    self.pack = Some(Err(err));
+                }
+                _ => {}
+            }
+        }
+        self.pack.clone()
+    }
+}
+
+/// Serves requests from an `AssetPack` instead of loose files.
+///
+/// The pack itself is fetched with the platform loader, requests made before
+/// it arrives are answered once it has. Clones share the same pack.
+#[derive(Clone)]
+pub struct PackLoader {
+    source: Rc<RefCell<PackSource>>,
+    requests: VecDeque<String>,
+}
+
+impl PackLoader {
+    /// loads the pack at `path`, e.g. `assets.ggpack`
+    pub fn new(path: &str) -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let mut loader = super::WebLoader::default();
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut loader = super::DiskLoader::default();
+        loader.request(path.to_string());
+        Self::with_source(PackSource {
+            loader: Some(Box::new(loader)),
+            pack: None,
+        })
+    }
+
+    pub fn from_pack(pack: AssetPack) -> Self {
+        Self::with_source(PackSource {
+            loader: None,
+            pack: Some(Ok(Rc::new(pack))),
+        })
+    }
+
+    fn with_source(source: PackSource) -> Self {
+        Self {
+            source: Rc::new(RefCell::new(source)),
+            requests: Default::default(),
+        }
+    }
+}
+
+impl Loader for PackLoader {
+    fn request(&mut self, path: String) {
+        self.requests.push_back(path);
+    }
+
+    fn poll(&mut self) -> Option<LoaderEvent> {
+        let pack = self.source.borrow_mut().poll()?;
+        let path = self.requests.pop_front()?;
+        let e = match pack {
+            Ok(pack) => match pack.get(&path) {
+                Some(data) => LoaderEvent::Load(path, data.to_vec()),
+                None => LoaderEvent::LoadFailed(path, AssetError::NotFound),
+            },
+            Err(err) => LoaderEvent::LoadFailed(path, err),
+        };
+        Some(e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AssetPack, AssetPackBuilder, PackLoader};
+    use crate::{AssetError, Loader, LoaderEvent};
+
+    #[test]
+    fn test_asset_pack() {
+        let pack = AssetPackBuilder::new()
+            .add("assets/a.txt", b"a".to_vec())
+            .add("./assets/maps/b.txt", b"bb".to_vec())
+            .add("empty", Vec::new())
+            .build();
+        assert_eq!(pack.get("assets/a.txt"), Some(&b"a"[..]));
+        assert_eq!(pack.get("assets/maps/b.txt"), Some(&b"bb"[..]));
+        assert_eq!(pack.get("./empty"), Some(&b""[..]));
+        assert_eq!(pack.get("missing"), None);
+
+        let bytes = AssetPackBuilder::new().add("a", b"a".to_vec()).to_bytes();
+        assert!(AssetPack::from_bytes(bytes[..bytes.len() - 1].to_vec()).is_err());
+        assert!(AssetPack::from_bytes(b"not a pack".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_pack_loader() {
+        let pack = AssetPackBuilder::new()
+            .add("assets/a.txt", b"a".to_vec())
+            .build();
+        let mut loader = PackLoader::from_pack(pack);
+        let mut other = loader.clone();
+        loader.request("assets/a.txt".into());
+        other.request("assets/b.txt".into());
+
+        match loader.poll() {
+            Some(LoaderEvent::Load(path, data)) => {
+                assert_eq!(path, "assets/a.txt");
+                assert_eq!(data, b"a");
+            }
+            e => panic!("unexpected event {e:?}"),
+        }
+        assert!(loader.poll().is_none());
+
+        match other.poll() {
+            Some(LoaderEvent::LoadFailed(path, err)) => {
+                assert_eq!(path, "assets/b.txt");
+                assert_eq!(err, AssetError::NotFound);
+            }
+            e => panic!("unexpected event {e:?}"),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+type Job<R> = Box<dyn FnOnce() -> R + Send>;
+
+/// A fixed number of threads running jobs, with results returned in completion order.
+///
+/// Threads are spawned on demand and exit when the pool is dropped.
+pub(crate) struct WorkerPool<R> {
+    size: usize,
+    jobs: Option<Sender<Job<R>>>,
+    job_receiver: Arc<Mutex<Receiver<Job<R>>>>,
+    results: Receiver<R>,
+    result_sender: Sender<R>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// the parallelism used when none is configured
+pub(crate) fn default_workers() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(4)
+}
+
+impl<R: Send + 'static> WorkerPool<R> {
+    pub fn new(size: usize) -> Self {
+        let (jobs, job_receiver) = mpsc::channel();
+        let (result_sender, results) = mpsc::channel();
+        Self {
+            size: size.max(1),
+            jobs: Some(jobs),
+            job_receiver: Arc::new(Mutex::new(job_receiver)),
+            results,
+            result_sender,
+            workers: Vec::new(),
+        }
+    }
+
+    pub fn spawn(&mut self, job: impl FnOnce() -> R + Send + 'static) {
+        if self.workers.len() < self.size {
+            let jobs = self.job_receiver.clone();
+            let results = self.result_sender.clone();
+            self.workers.push(thread::spawn(move || {
+                loop {
+                    let job = jobs.lock().unwrap().recv();
+                    let Ok(job) = job else {
+                        return;
+                    };
+                    if results.send(job()).is_err() {
+                        return;
+                    }
+                }
+            }));
+        }
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(Box::new(job));
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<R> {
+        self.results.try_recv().ok()
+    }
+}
+
+impl<R> Drop for WorkerPool<R> {
+    fn drop(&mut self) {
+        // closing the job channel lets idle workers exit
+        self.jobs.take();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{Arc, Barrier},
+        time::Duration,
+    };
+
+    use super::WorkerPool;
+
+    #[test]
+    fn test_worker_pool() {
+        let mut pool = WorkerPool::new(4);
+
+        // all four jobs must run at the same time to get past the barrier
+        let barrier = Arc::new(Barrier::new(4));
+        for i in 0..4 {
+            let barrier = barrier.clone();
+            pool.spawn(move || {
+                barrier.wait();
+                i
+            });
+        }
+
+        let start = web_time::Instant::now();
+        let mut results = Vec::new();
+        while results.len() < 4 {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            if let Some(r) = pool.try_recv() {
+                results.push(r);
+            }
+        }
+        results.sort();
+        assert_eq!(results, vec![0, 1, 2, 3]);
+        assert_eq!(pool.workers.len(), 4);
+    }
+}
@@ -0,0 +1,380 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc, str::from_utf8, sync::Arc};
+
+use eframe::glow::{self, HasContext as _};
+
+use crate::AssetError;
+
+use super::{AssetChange, AssetLoader, PollContext, TypedAssets};
+
+/// The stages of a shader source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl ShaderStage {
+    fn name(self) -> &'static str {
+        match self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::Fragment => "fragment",
+        }
+    }
+
+    fn gl_type(self) -> u32 {
+        match self {
+            ShaderStage::Vertex => glow::VERTEX_SHADER,
+            ShaderStage::Fragment => glow::FRAGMENT_SHADER,
+        }
+    }
+}
+
+/// The source of a stage as passed to gl, with the line in the file of each of its lines.
+#[derive(Debug, Clone, PartialEq)]
+struct StageSource {
+    source: String,
+    /// 0 for the lines added in front of the file
+    file_lines: Vec<usize>,
+}
+
+impl StageSource {
+    /// replaces the line numbers in a compile log with the lines in the file
+    ///
+    /// drivers report lines as `0:12(5)`, `0(12)` or `ERROR: 0:12:`, after the source string index.
+    fn map_log(&self, log: &str) -> String {
+        log.lines()
+            .map(|line| {
+                let Some((start, end, number)) = log_line_number(line) else {
+                    return line.to_string();
+                };
+                match self.file_lines.get(number.wrapping_sub(1)) {
+                    Some(&file_line) if file_line > 0 => format!("{}{file_line}{}", &line[..start], &line[end..]),
+                    _ => line.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// finds the line number following the source string index `0:` or `0(` in a log line
+fn log_line_number(line: &str) -> Option<(usize, usize, usize)> {
+    let bytes = line.as_bytes();
+    (0..bytes.len().saturating_sub(2)).find_map(|i| {
+        let at_word_start = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+        if !at_word_start || bytes[i] != b'0' || !matches!(bytes[i + 1], b':' | b'(') {
+            return None;
+        }
+        let start = i + 2;
+        let end = start + bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+        let number = line[start..end].parse().ok()?;
+        Some((start, end, number))
+    })
+}
+
+/// splits a shader file into its stages, each starting with a `#shader vertex` or
+/// `#shader fragment` line, with the lines before the first stage shared by both
+fn split_stages(source: &str) -> Result<[StageSource; 2], AssetError> {
+    let version = match cfg!(target_arch = "wasm32") {
+        true => "#version 300 es",
+        false => "#version 330",
+    };
+    let mut shared = Vec::new();
+    let mut stages: HashMap<&str, Vec<(usize, &str)>> = HashMap::new();
+    let mut current = None;
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        if let Some(stage) = line.trim().strip_prefix("#shader") {
+            let stage = stage.trim();
+            if !matches!(stage, "vertex" | "fragment") {
+                return Err(AssetError::Parse {
+                    line: line_number,
+                    column: 1,
+                    message: format!("unknown shader stage `{stage}`, expected `vertex` or `fragment`"),
+                });
+            }
+            if stages.insert(stage, Vec::new()).is_some() {
+                return Err(AssetError::Parse {
+                    line: line_number,
+                    column: 1,
+                    message: format!("the {stage} stage is defined twice"),
+                });
+            }
+            current = Some(stage);
+            continue;
+        }
+        if line.trim_start().starts_with("#version") {
+            return Err(AssetError::Parse {
+                line: line_number,
+                column: 1,
+                message: "the #version line is added for the platform and must be left out".to_string(),
+            });
+        }
+        match current {
+            Some(stage) => stages.get_mut(stage).unwrap().push((line_number, line)),
+            None => shared.push((line_number, line)),
+        }
+    }
+    let stage = |stage: ShaderStage| -> Result<StageSource, AssetError> {
+        let lines = stages.get(stage.name()).ok_or_else(|| {
+            AssetError::Decode(format!("the {} stage is missing, start it with `#shader {}`", stage.name(), stage.name()))
+        })?;
+        let mut prefix = vec![version];
+        // webgl2 has no default precision for floats in fragment shaders
+        if cfg!(target_arch = "wasm32") && stage == ShaderStage::Fragment {
+            prefix.push("precision mediump float;");
+        }
+        let lines: Vec<_> = prefix
+            .into_iter()
+            .map(|line| (0, line))
+            .chain(shared.iter().copied())
+            .chain(lines.iter().copied())
+            .collect();
+        Ok(StageSource {
+            source: lines.iter().map(|(_, line)| *line).collect::<Vec<_>>().join("\n"),
+            file_lines: lines.iter().map(|(file_line, _)| *file_line).collect(),
+        })
+    };
+    Ok([stage(ShaderStage::Vertex)?, stage(ShaderStage::Fragment)?])
+}
+
+/// A gl program compiled from a shader file, loaded with `GAssets::load::<GGShader>`.
+///
+/// The file holds both stages, each starting with a `#shader vertex` or `#shader fragment`
+/// line. Lines before the first stage are shared by both. The `#version` line is added
+/// for native gl or WebGL2, so shaders are written in the common subset of GLSL 330 and
+/// GLSL ES 300.
+///
+/// Compile and link errors fail the load, with the line numbers of the file. When running
+/// headless there is no gl context and the shader is only parsed.
+#[derive(Clone)]
+pub struct GGShader {
+    pub vertex_source: String,
+    pub fragment_source: String,
+    program: Option<Rc<CompiledProgram>>,
+}
+
+/// A linked program, deleted when the last clone of its shader is dropped.
+struct CompiledProgram {
+    gl: Arc<glow::Context>,
+    program: glow::Program,
+    uniforms: RefCell<HashMap<String, Option<glow::UniformLocation>>>,
+}
+
+impl Drop for CompiledProgram {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_program(self.program) };
+    }
+}
+
+impl GGShader {
+    /// parses `source` and compiles it with `gl`, if given
+    pub fn from_source(gl: Option<&Arc<glow::Context>>, source: &str) -> Result<Self, AssetError> {
+        let [vertex, fragment] = split_stages(source)?;
+        let program = match gl {
+            Some(gl) => Some(Rc::new(CompiledProgram {
+                gl: gl.clone(),
+                program: unsafe { Self::compile(gl, &vertex, &fragment)? },
+                uniforms: RefCell::new(HashMap::new()),
+            })),
+            None => None,
+        };
+        Ok(Self {
+            vertex_source: vertex.source,
+            fragment_source: fragment.source,
+            program,
+        })
+    }
+
+    unsafe fn compile(gl: &glow::Context, vertex: &StageSource, fragment: &StageSource) -> Result<glow::Program, AssetError> {
+        unsafe {
+            let program = gl.create_program().map_err(AssetError::Decode)?;
+            let mut shaders = Vec::new();
+            for (stage, source) in [(ShaderStage::Vertex, vertex), (ShaderStage::Fragment, fragment)] {
+                let shader = gl.create_shader(stage.gl_type()).map_err(AssetError::Decode)?;
+                gl.shader_source(shader, &source.source);
+                gl.compile_shader(shader);
+                if !gl.get_shader_compile_status(shader) {
+                    let log = source.map_log(&gl.get_shader_info_log(shader));
+                    gl.delete_shader(shader);
+                    shaders.into_iter().for_each(|shader| gl.delete_shader(shader));
+                    gl.delete_program(program);
+                    return Err(AssetError::Decode(format!("failed to compile {} shader:\n{log}", stage.name())));
+                }
+                gl.attach_shader(program, shader);
+                shaders.push(shader);
+            }
+            gl.link_program(program);
+            for shader in shaders {
+                gl.detach_shader(program, shader);
+                gl.delete_shader(shader);
+            }
+            if !gl.get_program_link_status(program) {
+                let log = gl.get_program_info_log(program);
+                gl.delete_program(program);
+                return Err(AssetError::Decode(format!("failed to link shader:\n{log}")));
+            }
+            Ok(program)
+        }
+    }
+
+    /// the compiled program, `None` when running headless
+    pub fn program(&self) -> Option<glow::Program> {
+        Some(self.program.as_ref()?.program)
+    }
+
+    /// makes the program current, which the `set_*` methods require
+    pub fn bind(&self) {
+        if let Some(compiled) = &self.program {
+            unsafe { compiled.gl.use_program(Some(compiled.program)) };
+        }
+    }
+
+    /// returns the location of the uniform `name`, looked up once
+    // locations are only `Copy` natively
+    #[allow(clippy::clone_on_copy)]
+    pub fn uniform(&self, name: &str) -> Option<glow::UniformLocation> {
+        let compiled = self.program.as_ref()?;
+        compiled
+            .uniforms
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| unsafe { compiled.gl.get_uniform_location(compiled.program, name) })
+            .clone()
+    }
+
+    fn set(&self, name: &str, set: impl FnOnce(&glow::Context, Option<&glow::UniformLocation>)) {
+        if let (Some(compiled), Some(location)) = (&self.program, self.uniform(name)) {
+            set(&compiled.gl, Some(&location));
+        }
+    }
+
+    pub fn set_i32(&self, name: &str, value: i32) {
+        self.set(name, |gl, location| unsafe { gl.uniform_1_i32(location, value) });
+    }
+
+    pub fn set_f32(&self, name: &str, value: f32) {
+        self.set(name, |gl, location| unsafe { gl.uniform_1_f32(location, value) });
+    }
+
+    pub fn set_vec2(&self, name: &str, value: glam::Vec2) {
+        self.set(name, |gl, location| unsafe { gl.uniform_2_f32(location, value.x, value.y) });
+    }
+
+    pub fn set_vec4(&self, name: &str, value: glam::Vec4) {
+        self.set(name, |gl, location| unsafe { gl.uniform_4_f32(location, value.x, value.y, value.z, value.w) });
+    }
+
+    pub fn set_mat3(&self, name: &str, value: &glam::Mat3) {
+        self.set(name, |gl, location| unsafe {
+            gl.uniform_matrix_3_f32_slice(location, false, &value.to_cols_array())
+        });
+    }
+
+    pub fn set_mat4(&self, name: &str, value: &glam::Mat4) {
+        self.set(name, |gl, location| unsafe {
+            gl.uniform_matrix_4_f32_slice(location, false, &value.to_cols_array())
+        });
+    }
+}
+
+impl AssetLoader for TypedAssets<GGShader> {
+    fn poll(&mut self, g:&mut PollContext) -> Option<AssetChange> {
+        self.poll(|load| {
+            let source = from_utf8(&load.data).map_err(|err| AssetError::Decode(err.to_string()))?;
+            GGShader::from_source(g.gl, source)
+        })
+        .map(AssetChange::from)
+    }
+
+    fn to_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn to_any_ref(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{split_stages, GGShader};
+    use crate::{
+        headless::fixture::{hot_reload, touch},
+        AssetError, GGHeadless,
+    };
+
+    const SHADER: &str = "// a shared comment
+uniform float u_time;
+#shader vertex
+in vec2 a_pos;
+void main() {
+    gl_Position = vec4(a_pos * u_time, 0.0, 1.0);
+}
+#shader fragment
+out vec4 out_color;
+void main() {
+    out_color = vec4(1.0);
+}
+";
+
+    #[test]
+    fn test_shader_source() {
+        let [vertex, fragment] = split_stages(SHADER).unwrap();
+        assert!(vertex.source.starts_with("#version "));
+        assert!(vertex.source.contains("uniform float u_time;\nin vec2 a_pos;"));
+        assert!(!vertex.source.contains("out_color"));
+        assert!(fragment.source.contains("uniform float u_time;\nout vec4 out_color;"));
+        assert_eq!(&vertex.file_lines[..4], [0, 1, 2, 4]);
+
+        // the fragment stage line with `out_color =` is line 11 of the file and 6 of the source
+        let logs = [
+            ("0:6(5): error: `out_colr' undeclared", "0:11(5): error: `out_colr' undeclared"),
+            ("0(6) : error C1008: undefined variable \"out_colr\"", "0(11) : error C1008: undefined variable \"out_colr\""),
+            ("ERROR: 0:6: 'out_colr' : undeclared identifier", "ERROR: 0:11: 'out_colr' : undeclared identifier"),
+            ("warning: something else", "warning: something else"),
+        ];
+        for (log, mapped) in logs {
+            assert_eq!(fragment.map_log(log), mapped);
+        }
+
+        let err = |source: &str| split_stages(source).unwrap_err();
+        assert!(matches!(err("#shader vertex\n"), AssetError::Decode(message) if message.contains("fragment stage")));
+        assert!(matches!(err("#shader geometry\n"), AssetError::Parse { line: 1, .. }));
+        assert!(matches!(err("#version 330\n#shader vertex\n"), AssetError::Parse { line: 1, .. }));
+        assert!(matches!(
+            err("#shader vertex\n#shader fragment\n#shader vertex\n"),
+            AssetError::Parse { line: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_shader_asset() {
+        let dir = std::env::temp_dir().join(format!("ggsdk_shader_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("quad.glsl");
+        std::fs::write(&path, SHADER).unwrap();
+
+        let file = path.to_string_lossy().to_string();
+        let mut headless = hot_reload(move |assets| {
+            assets.load::<GGShader>(&file, "quad");
+        });
+        {
+            let assets = headless.assets();
+            let shader = assets.get::<GGShader>("quad").unwrap();
+            // headless shaders are parsed but not compiled
+            assert!(shader.program().is_none());
+            assert!(shader.uniform("u_time").is_none());
+            assert!(shader.fragment_source.contains("out_color"));
+        }
+
+        std::fs::write(&path, SHADER.replace("vec4(1.0)", "vec4(0.5)")).unwrap();
+        touch(&path);
+        let reloaded = |h: &mut GGHeadless<_>| h.assets().get::<GGShader>("quad").unwrap().fragment_source.contains("vec4(0.5)");
+        assert!(headless.step_until(reloaded, Duration::from_secs(10)), "shader was not reloaded");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    collections::HashSet,
+    io::{self, Cursor},
+    path::{Component, Path},
+    rc::Rc,
+};
+
+use crate::{tileset_animations, AtlasGrid, GGAtlas, GGAsset};
+
+use super::{
+    AssetChange, AssetEvent, AssetLoader, Decode, Dependencies, Dependent, GAssets, Load, PollContext,
+    TypedAssets,
+};
+
+/// Resolves `.` and `..` in a path without touching the file system, so the
+/// same file requested from different maps has the same path.
+pub(super) fn normalize_path(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut absolute = false;
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => parts.push(prefix.as_os_str().to_string_lossy().to_string()),
+            Component::RootDir => absolute = true,
+            Component::CurDir => {}
+            Component::ParentDir => match parts.last() {
+                Some(last) if last != ".." => {
+                    parts.pop();
+                }
+                _ => parts.push("..".to_string()),
+            },
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+        }
+    }
+    let path = parts.join("/");
+    match absolute {
+        true => format!("/{path}"),
+        false => path,
+    }
+}
+
+/// Serves the map and the files it depends on, recording the paths which are not loaded yet.
+struct DependencyReader<'a> {
+    load: &'a Load,
+    dependencies: &'a Dependencies<'a>,
+    missing: Vec<String>,
+}
+
+impl tiled::ResourceReader for DependencyReader<'_> {
+    type Resource = Cursor<Vec<u8>>;
+    type Error = io::Error;
+
+    fn read_from(&mut self, path: &Path) -> Result<Self::Resource, Self::Error> {
+        let path = normalize_path(path);
+        if path == normalize_path(Path::new(&self.load.path)) {
+            return Ok(Cursor::new(self.load.data.clone()));
+        }
+        if let Some(data) = self.dependencies.get(&path) {
+            return Ok(Cursor::new(data.to_vec()));
+        }
+        self.missing.push(path.clone());
+        Err(io::Error::new(io::ErrorKind::NotFound, path))
+    }
+}
+
+/// The name of the `GGAtlas` loaded along with a map for the image of `tileset`.
+///
+/// This is the path of the image, relative to the working directory.
+pub fn tileset_atlas_name(tileset: &tiled::Tileset) -> Option<String> {
+    Some(image_atlas_name(tileset.image.as_ref()?))
+}
+
+/// The name of the `GGAtlas` loaded along with a map for `image`, which is
+/// the image of a tileset, a tile in an image collection or an image layer.
+pub fn image_atlas_name(image: &tiled::Image) -> String {
+    normalize_path(&image.source)
+}
+
+/// The images of `map` which are loaded as atlases along with it, with the
+/// tileset they are cut from or `None` if they are a single frame.
+fn map_images(map: &tiled::Map) -> Vec<(String, Option<&tiled::Tileset>)> {
+    fn layer_images(layer: tiled::Layer, images: &mut Vec<(String, Option<&tiled::Tileset>)>) {
+        match layer.layer_type() {
+            tiled::LayerType::Image(layer) => {
+                images.extend(layer.image.as_ref().map(|image| (image_atlas_name(image), None)));
+            }
+            tiled::LayerType::Group(group) => group.layers().for_each(|layer| layer_images(layer, images)),
+            _ => {}
+        }
+    }
+
+    let mut images = Vec::new();
+    for tileset in map.tilesets() {
+        match &tileset.image {
+            Some(image) => images.push((image_atlas_name(image), Some(tileset.as_ref()))),
+            None => images.extend(
+                tileset
+                    .tiles()
+                    .filter_map(|(_, tile)| Some((image_atlas_name(tile.image.as_ref()?), None))),
+            ),
+        }
+    }
+    map.layers().for_each(|layer| layer_images(layer, &mut images));
+    let mut seen = HashSet::new();
+    images.retain(|(path, _)| seen.insert(path.clone()));
+    images
+}
+
+fn decode_map(load: &Load, dependencies: &Dependencies) -> Decode<tiled::Map> {
+    let reader = DependencyReader {
+        load,
+        dependencies,
+        missing: Vec::new(),
+    };
+    let mut loader = tiled::Loader::with_reader(reader);
+    let map = loader.load_tmx_map(&load.path);
+    let missing = std::mem::take(&mut loader.reader_mut().missing);
+    let map = match map {
+        Ok(map) => map,
+        Err(_) if !missing.is_empty() => return Decode::Needs(missing),
+        Err(err) => return Decode::Done(Err(super::AssetError::Decode(err.to_string()))),
+    };
+    let images: Vec<_> = map_images(&map)
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|path| dependencies.get(path).is_none())
+        .collect();
+    if !images.is_empty() {
+        return Decode::Needs(images);
+    }
+    Decode::Done(Ok(map))
+}
+
+impl TypedAssets<tiled::Map> {
+    /// queues the images of `map` to be added to `GAssets` as atlases
+    fn add_map_atlases(&mut self, map: &GGAsset<tiled::Map>, g: &PollContext) {
+        for (path, tileset) in map_images(map) {
+            let Some(Some(Ok(data))) = self.dependencies.get(&path) else {
+                continue;
+            };
+            let image = match GGAtlas::decode_image(data) {
+                Ok(image) => image,
+                Err(err) => {
+                    tracing::warn!("failed to decode image {path} of {}: {err}", map.name);
+                    continue;
+                }
+            };
+            let atlas = match tileset {
+                Some(tileset) => {
+                    let cols = tileset.columns.max(1);
+                    let rows = tileset.tilecount.div_ceil(cols).max(1);
+                    let grid = AtlasGrid::new(cols, rows)
+                        .with_margin(tileset.margin)
+                        .with_spacing(tileset.spacing);
+                    let animations = tileset_animations(tileset).into_iter().map(|(_, animation)| animation);
+                    GGAtlas::from_grid(g.egui_ctx, &path, image, grid).with_animations(animations)
+                }
+                None => GGAtlas::from_grid(g.egui_ctx, &path, image, AtlasGrid::new(1, 1)),
+            };
+            let owner = map.name.clone();
+            self.dependents.push(Box::new(move |assets: &mut GAssets| {
+                assets.insert_dependent::<tiled::Map, GGAtlas>(&owner, &path, &path, atlas);
+            }));
+        }
+    }
+}
+
+impl AssetLoader for TypedAssets<tiled::Map> {
+    fn poll(&mut self, g:&mut PollContext) -> Option<AssetChange> {
+        let e = self.poll_with_dependencies(decode_map)?;
+        if let AssetEvent::Loaded(map) | AssetEvent::Reloaded(map) = &e {
+            self.add_map_atlases(map, g);
+        }
+        Some(e.into())
+    }
+
+    fn take_dependents(&mut self) -> Vec<Dependent> {
+        std::mem::take(&mut self.dependents)
+    }
+
+    fn to_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn to_any_ref(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl GAssets {
+    /// returns the atlas loaded along with a map for the image of `tileset`
+    pub fn tileset_atlas(&self, tileset: &tiled::Tileset) -> Option<Rc<GGAsset<GGAtlas>>> {
+        self.get::<GGAtlas>(&tileset_atlas_name(tileset)?)
+    }
+
+    /// returns the atlas loaded along with a map for `image`, see `image_atlas_name`
+    pub fn image_atlas(&self, image: &tiled::Image) -> Option<Rc<GGAsset<GGAtlas>>> {
+        self.get::<GGAtlas>(&image_atlas_name(image))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::Path, time::Duration};
+
+    use super::normalize_path;
+    use crate::{
+        headless::fixture::{hot_reload, touch},
+        AssetError, GGApp, GGAtlas, GGEngine, GGHeadless, InitContext, UpdateContext,
+    };
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path(Path::new("assets/maps/../basic.png")), "assets/basic.png");
+        assert_eq!(normalize_path(Path::new("./a/./b")), "a/b");
+        assert_eq!(normalize_path(Path::new("../a/../../b")), "../../b");
+        assert_eq!(normalize_path(Path::new("/a/b/../c")), "/a/c");
+    }
+
+    struct MapApp {
+        dir: std::path::PathBuf,
+    }
+
+    impl GGApp for MapApp {
+        fn init(&mut self, g: InitContext) {
+            let path = |file: &str| self.dir.join(file).to_string_lossy().to_string();
+            g.assets.begin_group("level");
+            g.assets.load::<tiled::Map>(&path("maps/level.tmx"), "level");
+            g.assets.end_group();
+            g.assets.load::<tiled::Map>(&path("maps/broken.tmx"), "broken");
+        }
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    #[test]
+    fn test_map_dependencies() {
+        let dir = std::env::temp_dir().join(format!("ggsdk_tiled_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("maps")).unwrap();
+        std::fs::copy("../examples/treasure_hunter/assets/basic_32x32.png", dir.join("basic.png")).unwrap();
+        std::fs::write(
+            dir.join("basic.tsx"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="basic" tilewidth="8" tileheight="8" tilecount="1024" columns="32">
+ <image source="basic.png" width="256" height="256"/>
+ <tile id="3">
+  <animation>
+   <frame tileid="3" duration="100"/>
+   <frame tileid="4" duration="250"/>
+  </animation>
+ </tile>
+</tileset>"#,
+        )
+        .unwrap();
+        let map = |tileset: &str| {
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="2" height="1" tilewidth="8" tileheight="8" infinite="0" nextlayerid="2" nextobjectid="1">
+ <tileset firstgid="1" source="{tileset}"/>
+ <layer id="1" name="ground" width="2" height="1">
+  <data encoding="csv">1,2</data>
+ </layer>
+</map>"#
+            )
+        };
+        std::fs::write(dir.join("maps/level.tmx"), map("../basic.tsx")).unwrap();
+        std::fs::write(dir.join("maps/broken.tmx"), map("../missing.tsx")).unwrap();
+
+        let mut headless = GGEngine::headless(MapApp { dir: dir.clone() }, Default::default());
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        let mut assets = headless.assets();
+
+        let level = assets.get::<tiled::Map>("level").unwrap();
+        let atlas = assets.tileset_atlas(&level.tilesets()[0]).unwrap();
+        assert_eq!((atlas.cols, atlas.rows), (32, 32));
+        assert_eq!(atlas.texture.size(), [256, 256]);
+        let animation = atlas.animation("3").unwrap();
+        let frames: Vec<_> = animation.frames.iter().map(|f| (f.index, f.duration)).collect();
+        assert_eq!(frames, [(3, 0.1), (4, 0.25)]);
+        assert!(matches!(
+            assets.error::<tiled::Map>("broken"),
+            Some(AssetError::Dependency { error, .. }) if **error == AssetError::NotFound
+        ));
+
+        let name = atlas.name.clone();
+        drop(atlas);
+        assert_eq!(assets.unload_group("level"), 2);
+        assert!(assets.get::<GGAtlas>(&name).is_none());
+
+        drop(assets);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_map_dependency_reload() {
+        let dir = std::env::temp_dir().join(format!("ggsdk_tiled_reload_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy("../examples/treasure_hunter/assets/basic_32x32.png", dir.join("basic.png")).unwrap();
+        let tileset = |duration: u32| {
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="basic" tilewidth="8" tileheight="8" tilecount="1024" columns="32">
+ <image source="basic.png" width="256" height="256"/>
+ <tile id="3">
+  <animation>
+   <frame tileid="3" duration="{duration}"/>
+   <frame tileid="4" duration="{duration}"/>
+  </animation>
+ </tile>
+</tileset>"#
+            )
+        };
+        std::fs::write(dir.join("basic.tsx"), tileset(100)).unwrap();
+        std::fs::write(
+            dir.join("level.tmx"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="1" height="1" tilewidth="8" tileheight="8" infinite="0" nextlayerid="2" nextobjectid="1">
+ <tileset firstgid="1" source="basic.tsx"/>
+ <layer id="1" name="ground" width="1" height="1">
+  <data encoding="csv">4</data>
+ </layer>
+</map>"#,
+        )
+        .unwrap();
+
+        let file = dir.join("level.tmx").to_string_lossy().to_string();
+        let mut headless = hot_reload(move |assets| {
+            assets.load::<tiled::Map>(&file, "level");
+        });
+        let duration = |h: &mut GGHeadless<_>| {
+            let assets = h.assets();
+            let level = assets.get::<tiled::Map>("level").unwrap();
+            let atlas = assets.tileset_atlas(&level.tilesets()[0]).unwrap();
+            atlas.animation("3").unwrap().frames[0].duration
+        };
+        assert_eq!(duration(&mut headless), 0.1);
+
+        // only the tileset changes, the map is decoded again with it
+        std::fs::write(dir.join("basic.tsx"), tileset(200)).unwrap();
+        touch(dir.join("basic.tsx"));
+        let reloaded = |h: &mut GGHeadless<_>| duration(h) == 0.2;
+        assert!(headless.step_until(reloaded, Duration::from_secs(10)), "map was not reloaded");
+        assert_eq!(headless.assets().take_reloaded(), vec!["level".to_string()]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 
-use futures::channel::oneshot;
+use futures::channel::{mpsc, oneshot};
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::{spawn_local, JsFuture};
-use web_sys::{js_sys, Request, RequestInit, Response};
+use web_sys::{js_sys, ReadableStreamDefaultReader, Request, RequestInit, Response};
 
-use crate::{Loader, LoaderEvent};
+use crate::{AssetError, Loader, LoaderEvent};
 
 #[derive(Default)]
 pub struct WebLoader {
     pending_requests: HashMap<String, oneshot::Receiver<LoaderEvent>>,
     new_requests: Vec<String>,
+    progress: Option<(mpsc::UnboundedSender<LoaderEvent>, mpsc::UnboundedReceiver<LoaderEvent>)>,
 }
 
 impl WebLoader {
@@ -18,10 +19,48 @@ impl WebLoader {
         Self {
             pending_requests: Default::default(),
             new_requests: Default::default(),
+            progress: None,
         }
     }
 
-    async fn load_asset_async(path: String) -> LoaderEvent {
+    /// streams response bodies, sending a `LoaderEvent::Progress` for every chunk received
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress.then(mpsc::unbounded);
+        self
+    }
+
+    /// reads the body of `response` chunk by chunk, reporting the bytes received so far
+    async fn read_body_with_progress(
+        path: &str,
+        response: &Response,
+        progress: &mpsc::UnboundedSender<LoaderEvent>,
+    ) -> Result<Vec<u8>, AssetError> {
+        let network = |err: wasm_bindgen::JsValue| AssetError::Network(format!("{err:?}"));
+        let total = response
+            .headers()
+            .get("content-length")
+            .ok()
+            .flatten()
+            .and_then(|len| len.parse::<u64>().ok());
+        let Some(body) = response.body() else {
+            return Ok(Vec::new());
+        };
+        let reader: ReadableStreamDefaultReader = body.get_reader().unchecked_into();
+        let mut data = Vec::new();
+        loop {
+            let chunk = JsFuture::from(reader.read()).await.map_err(network)?;
+            let done = js_sys::Reflect::get(&chunk, &"done".into()).map_err(network)?;
+            if done.as_bool().unwrap_or(true) {
+                break;
+            }
+            let value = js_sys::Reflect::get(&chunk, &"value".into()).map_err(network)?;
+            data.extend(js_sys::Uint8Array::new(&value).to_vec());
+            let _ = progress.unbounded_send(LoaderEvent::Progress(path.to_string(), data.len() as u64, total));
+        }
+        Ok(data)
+    }
+
+    async fn load_asset_async(path: String, progress: Option<mpsc::UnboundedSender<LoaderEvent>>) -> LoaderEvent {
         let opts = RequestInit::new();
         opts.set_method("GET");
 
@@ -32,21 +71,33 @@ impl WebLoader {
         let window = web_sys::window().unwrap();
         let response_js_value = match JsFuture::from(window.fetch_with_request(&request)).await {
             Ok(resp) => resp,
-            Err(_) => return LoaderEvent::LoadFailed(path),
+            Err(err) => return LoaderEvent::LoadFailed(path, AssetError::Network(format!("{err:?}"))),
         };
 
         let response: Response = response_js_value.dyn_into().unwrap();
 
         // Check if the response is successful
         if !response.ok() {
-            return LoaderEvent::LoadFailed(path);
+            let error = match response.status() {
+                404 => AssetError::NotFound,
+                401 | 403 => AssetError::PermissionDenied,
+                status => AssetError::Http(status),
+            };
+            return LoaderEvent::LoadFailed(path, error);
+        }
+
+        if let Some(progress) = progress {
+            return match WebLoader::read_body_with_progress(&path, &response, &progress).await {
+                Ok(data) => LoaderEvent::Load(path, data),
+                Err(err) => LoaderEvent::LoadFailed(path, err),
+            };
         }
 
         // Get the response body as an array buffer
         let array_buffer_promise = response.array_buffer().unwrap();
         let array_buffer_js_value = match JsFuture::from(array_buffer_promise).await {
             Ok(buffer) => buffer,
-            Err(_) => return LoaderEvent::LoadFailed(path),
+            Err(err) => return LoaderEvent::LoadFailed(path, AssetError::Network(format!("{err:?}"))),
         };
 
         let array_buffer = js_sys::Uint8Array::new(&array_buffer_js_value);
@@ -56,9 +107,10 @@ impl WebLoader {
     }
 
     fn spawn_asset_load(&mut self, path: String, sender: oneshot::Sender<LoaderEvent>) {
+        let progress = self.progress.as_ref().map(|(sender, _)| sender.clone());
         // Spawn the async asset loading task
         spawn_local(async move {
-            let event = WebLoader::load_asset_async(path).await;
+            let event = WebLoader::load_asset_async(path, progress).await;
             let _ = sender.send(event);
         });
     }
@@ -70,6 +122,12 @@ impl Loader for WebLoader {
     }
 
     fn poll(&mut self) -> Option<LoaderEvent> {
+        if let Some((_, receiver)) = self.progress.as_mut()
+            && let Ok(Some(e)) = receiver.try_next()
+        {
+            return Some(e);
+        }
+
         let mut processed = None;
         let mut event = None;
 
@@ -89,7 +147,10 @@ impl Loader for WebLoader {
                     }
                 }
                 Err(_) => {
-                    event = Some(LoaderEvent::LoadFailed(path.to_owned()));
+                    event = Some(LoaderEvent::LoadFailed(
+                        path.to_owned(),
+                        AssetError::Network("request was cancelled".to_string()),
+                    ));
                     processed = Some(path.to_string());
                     break;
                 }
@@ -1,31 +1,463 @@
-use eframe::egui::{ColorImage, Context, TextureHandle, TextureId, TextureOptions};
+use std::collections::HashMap;
+
+use eframe::egui::{ColorImage, Context, Pos2, Rect, TextureHandle, TextureId, TextureOptions, Vec2};
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{AnimationMode, AssetError, GGAnimation};
+
+/// A sprite in an atlas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasFrame {
+    /// empty for the unnamed frames of a grid
+    pub name: String,
+    /// the frame in the image, in pixels
+    pub rect: Rect,
+    /// the point the frame is drawn around, relative to its size, (0.5, 0.5) is the center
+    pub pivot: Vec2,
+    /// how long the frame is shown in an animation, in seconds
+    pub duration: Option<f32>,
+}
+
+/// Cells of equal size in an image, numbered left to right and top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct AtlasGrid {
+    pub cols: u32,
+    pub rows: u32,
+    /// pixels around the cells
+    #[serde(default)]
+    pub margin: u32,
+    /// pixels between the cells
+    #[serde(default)]
+    pub spacing: u32,
+}
+
+impl AtlasGrid {
+    pub fn new(cols: u32, rows: u32) -> Self {
+        Self {
+            cols,
+            rows,
+            margin: 0,
+            spacing: 0,
+        }
+    }
+
+    pub fn with_margin(mut self, margin: u32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// the cells of the grid in an image of `size`
+    pub fn frames(&self, size: [usize; 2]) -> Vec<AtlasFrame> {
+        let (cols, rows) = (self.cols.max(1), self.rows.max(1));
+        let (margin, spacing) = (self.margin as f32, self.spacing as f32);
+        let w = (size[0] as f32 - 2.0 * margin - (cols - 1) as f32 * spacing) / cols as f32;
+        let h = (size[1] as f32 - 2.0 * margin - (rows - 1) as f32 * spacing) / rows as f32;
+        (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let min = Pos2::new(
+                    margin + col as f32 * (w + spacing),
+                    margin + row as f32 * (h + spacing),
+                );
+                AtlasFrame {
+                    name: String::new(),
+                    rect: Rect::from_min_size(min, Vec2::new(w, h)),
+                    pivot: Vec2::splat(0.5),
+                    duration: None,
+                }
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone)]
 pub struct GGAtlas {
     pub texture:TextureHandle,
-    pub cols:u8,
-    pub rows:u8,
-    pub name:String
+    /// the size of the grid the atlas was created from, 0 when its frames were given
+    pub cols:u32,
+    pub rows:u32,
+    pub name:String,
+    frames: Vec<AtlasFrame>,
+    names: HashMap<String, usize>,
+    animations: HashMap<String, GGAnimation>,
 }
 
 impl GGAtlas {
     pub fn new(ctx: &Context, name:impl Into<String>, image_data: &[u8], cols:u8, rows:u8) -> Self {
-        let name = name.into();
-        let img = image::load_from_memory(image_data).unwrap();
+        Self::try_new(ctx, name, image_data, cols, rows).unwrap()
+    }
+
+    pub fn try_new(ctx: &Context, name:impl Into<String>, image_data: &[u8], cols:u8, rows:u8) -> Result<Self, image::ImageError> {
+        let img = Self::decode_image(image_data)?;
+        Ok(Self::from_image(ctx, name, img, cols, rows))
+    }
+
+    /// decodes an encoded image, e.g. a png, without touching the egui context
+    pub fn decode_image(image_data: &[u8]) -> Result<ColorImage, image::ImageError> {
+        let img = image::load_from_memory(image_data)?;
         let size = [img.width() as _, img.height() as _];
         let image_buffer = img.to_rgba8();
         let pixels = image_buffer.as_flat_samples();
-        let img = ColorImage::from_rgba_premultiplied(size, pixels.as_slice());
+        Ok(ColorImage::from_rgba_premultiplied(size, pixels.as_slice()))
+    }
+
+    /// the size of the texture in bytes
+    pub fn memory_size(&self) -> usize {
+        let [w, h] = self.texture.size();
+        w * h * 4
+    }
+
+    pub fn from_image(ctx: &Context, name:impl Into<String>, img: ColorImage, cols:u8, rows:u8) -> Self {
+        Self::from_grid(ctx, name, img, AtlasGrid::new(cols as u32, rows as u32))
+    }
+
+    pub fn from_grid(ctx: &Context, name:impl Into<String>, img: ColorImage, grid: AtlasGrid) -> Self {
+        let frames = grid.frames(img.size);
+        let mut atlas = Self::from_frames(ctx, name, img, frames);
+        atlas.cols = grid.cols;
+        atlas.rows = grid.rows;
+        atlas
+    }
+
+    pub fn from_frames(ctx: &Context, name:impl Into<String>, img: ColorImage, frames: Vec<AtlasFrame>) -> Self {
+        let name = name.into();
         let img = ctx.load_texture(name.clone(), img, TextureOptions::NEAREST);
-        
+        let names = frames
+            .iter()
+            .enumerate()
+            .filter(|(_, frame)| !frame.name.is_empty())
+            .map(|(i, frame)| (frame.name.clone(), i))
+            .collect();
+
         Self {
             name,
             texture:img,
-            cols,
-            rows
+            cols: 0,
+            rows: 0,
+            frames,
+            names,
+            animations: HashMap::new(),
+        }
+    }
+
+    /// adds animations of the frames of the atlas, replacing any with the same name
+    pub fn with_animations(mut self, animations: impl IntoIterator<Item = GGAnimation>) -> Self {
+        for animation in animations {
+            self.animations.insert(animation.name.clone(), animation);
         }
+        self
+    }
+
+    pub fn animation(&self, name: &str) -> Option<&GGAnimation> {
+        self.animations.get(name)
+    }
+
+    pub fn animations(&self) -> impl Iterator<Item = &GGAnimation> {
+        self.animations.values()
     }
-    
+
     pub fn texture_id(&self) -> TextureId {
         self.texture.id()
     }
+
+    pub fn frames(&self) -> &[AtlasFrame] {
+        &self.frames
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&AtlasFrame> {
+        self.frames.get(index)
+    }
+
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    pub fn named_frame(&self, name: &str) -> Option<&AtlasFrame> {
+        self.frame(self.frame_index(name)?)
+    }
+
+    /// the texture coordinates of a frame, from 0 to 1
+    pub fn uv(&self, frame: &AtlasFrame) -> Rect {
+        let [w, h] = self.texture.size();
+        let size = Vec2::new(w as f32, h as f32);
+        Rect::from_min_max((frame.rect.min.to_vec2() / size).to_pos2(), (frame.rect.max.to_vec2() / size).to_pos2())
+    }
+}
+
+/// Frames of an atlas read from a sidecar JSON file, along with the path of its image.
+///
+/// Reads the JSON exported by TexturePacker and Aseprite, as a hash or an array,
+/// and the ggsdk format, which lists frames and optionally a grid:
+///
+/// `{ "image": "hero.png", "grid": { "cols": 4, "rows": 2, "margin": 1, "spacing": 2 },
+///    "frames": [{ "name": "idle", "rect": [0, 0, 16, 24], "pivot": [0.5, 1.0] }] }`
+///
+/// Grid cells come first and are unnamed. Rotated frames are not supported, and
+/// trimmed frames are drawn without the trimmed area.
+///
+/// The frame tags of Aseprite become animations, frames without a duration last 0.1 seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasMeta {
+    /// the image path, relative to the JSON file
+    pub image: String,
+    pub grid: Option<AtlasGrid>,
+    pub frames: Vec<AtlasFrame>,
+    pub animations: Vec<GGAnimation>,
+}
+
+#[derive(Deserialize)]
+struct PackerRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+#[derive(Deserialize)]
+struct PackerPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+struct PackerFrame {
+    #[serde(default)]
+    filename: String,
+    frame: PackerRect,
+    #[serde(default)]
+    rotated: bool,
+    pivot: Option<PackerPoint>,
+    /// in milliseconds
+    duration: Option<f32>,
+}
+
+/// Frames by name in the order of the file, or a list of frames.
+struct PackerFrames(Vec<PackerFrame>);
+
+impl<'de> Deserialize<'de> for PackerFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = PackerFrames;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a map or a list of frames")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some((name, mut frame)) = map.next_entry::<String, PackerFrame>()? {
+                    frame.filename = name;
+                    frames.push(frame);
+                }
+                Ok(PackerFrames(frames))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some(frame) = seq.next_element()? {
+                    frames.push(frame);
+                }
+                Ok(PackerFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_any(FramesVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct PackerTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+    /// the number of times to play the animation, forever when missing
+    repeat: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PackerMeta {
+    image: String,
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<PackerTag>,
+}
+
+impl PackerTag {
+    fn animation(&self, frames: &[AtlasFrame]) -> GGAnimation {
+        let mut positions: Vec<usize> = (self.from..=self.to.min(frames.len().saturating_sub(1))).collect();
+        if self.direction.ends_with("reverse") {
+            positions.reverse();
+        }
+        let mode = match (self.direction.starts_with("pingpong"), self.repeat.as_deref()) {
+            (_, Some("1")) => AnimationMode::Once,
+            (true, _) => AnimationMode::PingPong,
+            (false, _) => AnimationMode::Loop,
+        };
+        let frames = positions
+            .into_iter()
+            .map(|index| (index, frames[index].duration.unwrap_or(0.1)));
+        GGAnimation::new(self.name.clone(), frames, mode)
+    }
+}
+
+#[derive(Deserialize)]
+struct PackerFile {
+    frames: PackerFrames,
+    meta: PackerMeta,
+}
+
+fn center() -> [f32; 2] {
+    [0.5, 0.5]
+}
+
+#[derive(Deserialize)]
+struct GGFrame {
+    name: String,
+    rect: [f32; 4],
+    #[serde(default = "center")]
+    pivot: [f32; 2],
+    /// in seconds
+    duration: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct GGFile {
+    image: String,
+    grid: Option<AtlasGrid>,
+    #[serde(default)]
+    frames: Vec<GGFrame>,
+}
+
+impl AtlasMeta {
+    pub fn from_json(data: &[u8]) -> Result<Self, AssetError> {
+        #[derive(Deserialize)]
+        struct Probe {
+            meta: Option<serde::de::IgnoredAny>,
+        }
+        // TexturePacker and Aseprite keep the image path in `meta`
+        let probe: Probe = serde_json::from_slice(data)?;
+        if probe.meta.is_none() {
+            let file: GGFile = serde_json::from_slice(data)?;
+            return Ok(Self {
+                image: file.image,
+                grid: file.grid,
+                frames: file
+                    .frames
+                    .into_iter()
+                    .map(|frame| {
+                        let [x, y, w, h] = frame.rect;
+                        AtlasFrame {
+                            name: frame.name,
+                            rect: Rect::from_min_size(Pos2::new(x, y), Vec2::new(w, h)),
+                            pivot: frame.pivot.into(),
+                            duration: frame.duration,
+                        }
+                    })
+                    .collect(),
+                animations: Vec::new(),
+            });
+        }
+        let file: PackerFile = serde_json::from_slice(data)?;
+        let frames = file
+            .frames
+            .0
+            .into_iter()
+            .map(|frame| {
+                if frame.rotated {
+                    return Err(AssetError::Decode(format!("frame {} is rotated, which is not supported", frame.filename)));
+                }
+                let PackerRect { x, y, w, h } = frame.frame;
+                Ok(AtlasFrame {
+                    name: frame.filename,
+                    rect: Rect::from_min_size(Pos2::new(x, y), Vec2::new(w, h)),
+                    pivot: frame.pivot.map(|p| Vec2::new(p.x, p.y)).unwrap_or(Vec2::splat(0.5)),
+                    duration: frame.duration.map(|ms| ms / 1000.0),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let animations = file.meta.frame_tags.iter().map(|tag| tag.animation(&frames)).collect();
+        Ok(Self {
+            image: file.meta.image,
+            grid: None,
+            frames,
+            animations,
+        })
+    }
+
+    /// the grid cells followed by the listed frames, in an image of `size`
+    pub fn all_frames(&self, size: [usize; 2]) -> Vec<AtlasFrame> {
+        let mut frames = self.grid.map(|grid| grid.frames(size)).unwrap_or_default();
+        frames.extend(self.frames.iter().cloned());
+        frames
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eframe::egui::{Pos2, Rect, Vec2};
+
+    use super::{AtlasGrid, AtlasMeta};
+    use crate::{AnimationMode, AssetError};
+
+    #[test]
+    fn test_atlas_grid() {
+        let frames = AtlasGrid::new(2, 2).with_margin(1).with_spacing(2).frames([20, 12]);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].rect, Rect::from_min_size(Pos2::new(1.0, 1.0), Vec2::new(8.0, 4.0)));
+        assert_eq!(frames[3].rect, Rect::from_min_size(Pos2::new(11.0, 7.0), Vec2::new(8.0, 4.0)));
+    }
+
+    #[test]
+    fn test_atlas_meta() {
+        let hash = br#"{
+            "frames": {
+                "walk 1": { "frame": { "x": 16, "y": 0, "w": 16, "h": 24 }, "rotated": false, "duration": 100 },
+                "walk 0": { "frame": { "x": 0, "y": 0, "w": 16, "h": 24 }, "pivot": { "x": 0.5, "y": 1 } }
+            },
+            "meta": { "image": "hero.png", "frameTags": [{ "name": "walk", "from": 0, "to": 1, "direction": "reverse" }] }
+        }"#;
+        let meta = AtlasMeta::from_json(hash).unwrap();
+        assert_eq!(meta.image, "hero.png");
+        let names: Vec<_> = meta.frames.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["walk 1", "walk 0"]);
+        assert_eq!(meta.frames[0].duration, Some(0.1));
+        assert_eq!(meta.frames[1].pivot, Vec2::new(0.5, 1.0));
+        let walk = &meta.animations[0];
+        assert_eq!((walk.name.as_str(), walk.mode), ("walk", AnimationMode::Loop));
+        let frames: Vec<_> = walk.frames.iter().map(|f| (f.index, f.duration)).collect();
+        assert_eq!(frames, [(1, 0.1), (0, 0.1)]);
+
+        let array = br#"{
+            "frames": [{ "filename": "coin", "frame": { "x": 2, "y": 3, "w": 4, "h": 5 } }],
+            "meta": { "image": "items.png" }
+        }"#;
+        let meta = AtlasMeta::from_json(array).unwrap();
+        assert_eq!(meta.frames[0].name, "coin");
+        assert_eq!(meta.frames[0].rect, Rect::from_min_size(Pos2::new(2.0, 3.0), Vec2::new(4.0, 5.0)));
+
+        let ggsdk = br#"{
+            "image": "tiles.png",
+            "grid": { "cols": 2, "rows": 1 },
+            "frames": [{ "name": "door", "rect": [0, 8, 8, 16], "pivot": [0.5, 1] }]
+        }"#;
+        let meta = AtlasMeta::from_json(ggsdk).unwrap();
+        let frames = meta.all_frames([16, 24]);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].rect.min, Pos2::new(8.0, 0.0));
+        assert_eq!(frames[2].name, "door");
+
+        let broken = b"{\n  \"image\": 3\n}";
+        assert!(matches!(AtlasMeta::from_json(broken), Err(AssetError::Parse { line: 2, .. })));
+    }
 }
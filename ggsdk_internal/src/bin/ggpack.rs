@@ -0,0 +1,27 @@
+//! Packs a directory into an asset pack.
+//!
+//! `ggpack assets assets.ggpack` makes `assets/sfx/coin.mp3` loadable from
+//! `assets.ggpack` with `AssetSource::Pack` in `GGRunOptions::asset_sources`.
+use std::process::ExitCode;
+
+use ggsdk_internal::AssetPackBuilder;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [dir, output] = args.as_slice() else {
+        eprintln!("usage: ggpack <dir> <output>");
+        return ExitCode::FAILURE;
+    };
+    let builder = match AssetPackBuilder::new().add_dir(dir) {
+        Ok(builder) => builder,
+        Err(err) => {
+            eprintln!("failed to read {dir}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(err) = builder.write(output) {
+        eprintln!("failed to write {output}: {err}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
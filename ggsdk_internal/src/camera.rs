@@ -0,0 +1,230 @@
+use eframe::egui::{pos2, Pos2, Rect, Vec2};
+use glam::{Affine2, Mat4};
+
+use crate::GGSprite;
+
+/// A 2D camera mapping world space to screen points.
+///
+/// Use `transform_rect` and `transform_sprite` to draw world space with `GGPainter`,
+/// or `view` and `projection` on the glow path, e.g. with `SpriteBatch::draw_with_view`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GGCamera2D {
+    /// the world position shown at the center of the viewport
+    pub position: Pos2,
+    /// screen points per world unit
+    pub zoom: f32,
+    /// clockwise rotation of the camera, in radians, the world appears rotated the other way
+    pub rotation: f32,
+    /// the part of the screen the camera draws to, in points
+    pub viewport: Rect,
+    shake_strength: f32,
+    shake_duration: f32,
+    shake_remaining: f32,
+    time: f32,
+}
+
+impl Default for GGCamera2D {
+    fn default() -> Self {
+        Self::new(Rect::ZERO)
+    }
+}
+
+impl GGCamera2D {
+    pub fn new(viewport: Rect) -> Self {
+        Self {
+            position: Pos2::ZERO,
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport,
+            shake_strength: 0.0,
+            shake_duration: 0.0,
+            shake_remaining: 0.0,
+            time: 0.0,
+        }
+    }
+
+    pub fn with_position(mut self, position: Pos2) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// advances the screen shake by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+        self.shake_remaining = (self.shake_remaining - dt).max(0.0);
+    }
+
+    /// moves the camera towards `target`, covering more of the distance the higher `smoothing` is
+    ///
+    /// the movement does not depend on the frame rate, an infinite `smoothing` snaps to `target`
+    pub fn follow(&mut self, target: Pos2, smoothing: f32, dt: f32) {
+        let t = 1.0 - (-smoothing * dt).exp();
+        self.position = self.position.lerp(target, t);
+    }
+
+    /// shakes the camera by up to `strength` screen points, fading out over `duration` seconds
+    ///
+    /// a stronger shake replaces a weaker one that is still going on
+    pub fn shake(&mut self, strength: f32, duration: f32) {
+        if strength >= self.shake_strength * self.shake_fade() {
+            self.shake_strength = strength;
+            self.shake_duration = duration;
+            self.shake_remaining = duration;
+        }
+    }
+
+    fn shake_fade(&self) -> f32 {
+        match self.shake_duration > 0.0 {
+            true => self.shake_remaining / self.shake_duration,
+            false => 0.0,
+        }
+    }
+
+    /// the current screen shake offset, in screen points
+    pub fn shake_offset(&self) -> Vec2 {
+        let strength = self.shake_strength * self.shake_fade();
+        if strength <= 0.0 {
+            return Vec2::ZERO;
+        }
+        // sums of sines at unrelated frequencies look random but stay smooth
+        let t = self.time;
+        let x = (t * 47.0).sin() * 0.6 + (t * 83.0 + 1.3).sin() * 0.4;
+        let y = (t * 53.0 + 2.1).sin() * 0.6 + (t * 71.0 + 0.7).sin() * 0.4;
+        Vec2::new(x, y) * strength * std::f32::consts::FRAC_1_SQRT_2
+    }
+
+    /// the transform from world space to screen points
+    pub fn view(&self) -> Affine2 {
+        let center = self.viewport.center() + self.shake_offset();
+        Affine2::from_translation(glam::vec2(center.x, center.y))
+            * Affine2::from_scale_angle_translation(glam::Vec2::splat(self.zoom), -self.rotation, glam::Vec2::ZERO)
+            * Affine2::from_translation(glam::vec2(-self.position.x, -self.position.y))
+    }
+
+    /// the transform from world space to gl clip space, for a gl viewport covering `viewport`
+    pub fn projection(&self) -> Mat4 {
+        let Rect { min, max } = self.viewport;
+        let view = self.view();
+        let view = Mat4::from_cols(
+            view.matrix2.x_axis.extend(0.0).extend(0.0),
+            view.matrix2.y_axis.extend(0.0).extend(0.0),
+            glam::Vec4::Z,
+            view.translation.extend(0.0).extend(1.0),
+        );
+        Mat4::orthographic_rh_gl(min.x, max.x, max.y, min.y, -1.0, 1.0) * view
+    }
+
+    pub fn world_to_screen(&self, world: Pos2) -> Pos2 {
+        let screen = self.view().transform_point2(glam::vec2(world.x, world.y));
+        pos2(screen.x, screen.y)
+    }
+
+    pub fn screen_to_world(&self, screen: Pos2) -> Pos2 {
+        let world = self.view().inverse().transform_point2(glam::vec2(screen.x, screen.y));
+        pos2(world.x, world.y)
+    }
+
+    /// transforms a world space rect to screen points, the bounds of it when the camera is rotated
+    pub fn transform_rect(&self, world: Rect) -> Rect {
+        let corners = [world.left_top(), world.right_top(), world.right_bottom(), world.left_bottom()];
+        Rect::from_points(&corners.map(|corner| self.world_to_screen(corner)))
+    }
+
+    /// transforms a sprite placed in world space to screen points
+    ///
+    /// a sprite without size is sized in world units, one per frame pixel
+    pub fn transform_sprite(&self, sprite: &GGSprite) -> GGSprite {
+        GGSprite {
+            pos: self.world_to_screen(sprite.pos),
+            scale: sprite.scale * self.zoom,
+            rotation: sprite.rotation - self.rotation,
+            ..*sprite
+        }
+    }
+
+    /// the part of the world inside the viewport, the bounds of it when the camera is rotated
+    pub fn visible_rect(&self) -> Rect {
+        let Rect { min, max } = self.viewport;
+        let corners = [min, pos2(max.x, min.y), max, pos2(min.x, max.y)];
+        Rect::from_points(&corners.map(|corner| self.screen_to_world(corner)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eframe::egui::{pos2, vec2, Pos2, Rect};
+
+    use super::GGCamera2D;
+    use crate::GGSprite;
+
+    fn close(a: Pos2, b: Pos2) -> bool {
+        (a - b).length() < 1e-3
+    }
+
+    #[test]
+    fn test_camera_transforms() {
+        let viewport = Rect::from_min_size(pos2(0.0, 0.0), vec2(800.0, 600.0));
+        let camera = GGCamera2D::new(viewport).with_position(pos2(10.0, 5.0)).with_zoom(32.0);
+        assert!(close(camera.world_to_screen(pos2(10.0, 5.0)), pos2(400.0, 300.0)));
+        assert!(close(camera.world_to_screen(pos2(11.0, 5.0)), pos2(432.0, 300.0)));
+        assert!(close(camera.screen_to_world(pos2(0.0, 0.0)), pos2(-2.5, -4.375)));
+
+        let tile = camera.transform_rect(Rect::from_min_size(pos2(10.0, 5.0), vec2(1.0, 1.0)));
+        assert!(close(tile.min, pos2(400.0, 300.0)) && close(tile.max, pos2(432.0, 332.0)));
+        let visible = camera.visible_rect();
+        assert!(close(visible.min, pos2(-2.5, -4.375)) && close(visible.max, pos2(22.5, 14.375)));
+
+        // the projection maps the viewport corners to the corners of clip space
+        let clip = camera.projection().project_point3(glam::vec3(-2.5, -4.375, 0.0));
+        assert!(close(pos2(clip.x, clip.y), pos2(-1.0, 1.0)));
+
+        // rotating the camera clockwise turns the world counter clockwise
+        let rotated = camera.clone().with_rotation(std::f32::consts::FRAC_PI_2);
+        assert!(close(rotated.world_to_screen(pos2(11.0, 5.0)), pos2(400.0, 268.0)));
+        assert!(close(rotated.screen_to_world(rotated.world_to_screen(pos2(3.0, 4.0))), pos2(3.0, 4.0)));
+        let sprite = rotated.transform_sprite(&GGSprite::at(pos2(11.0, 5.0)).with_rotation(1.0));
+        assert!(close(sprite.pos, pos2(400.0, 268.0)));
+        assert_eq!(sprite.scale, vec2(32.0, 32.0));
+        assert!((sprite.rotation - (1.0 - std::f32::consts::FRAC_PI_2)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_camera_follow_and_shake() {
+        let mut camera = GGCamera2D::default();
+        camera.follow(pos2(100.0, 0.0), 2.0_f32.ln(), 1.0);
+        assert!(close(camera.position, pos2(50.0, 0.0)));
+        // two half steps move as far as one whole step
+        camera.follow(pos2(100.0, 0.0), 2.0_f32.ln(), 0.5);
+        camera.follow(pos2(100.0, 0.0), 2.0_f32.ln(), 0.5);
+        assert!(close(camera.position, pos2(75.0, 0.0)));
+        camera.follow(pos2(0.0, 0.0), f32::INFINITY, 0.1);
+        assert_eq!(camera.position, pos2(0.0, 0.0));
+
+        camera.shake(8.0, 1.0);
+        let mut moved = false;
+        for _ in 0..10 {
+            camera.update(0.05);
+            let offset = camera.shake_offset();
+            assert!(offset.length() <= 8.0);
+            moved |= offset.length() > 0.1;
+        }
+        assert!(moved);
+        // a weaker shake does not cut a stronger one short
+        camera.shake(1.0, 0.1);
+        camera.update(0.2);
+        assert_ne!(camera.shake_offset(), vec2(0.0, 0.0));
+        camera.update(1.0);
+        assert_eq!(camera.shake_offset(), vec2(0.0, 0.0));
+        assert!(close(camera.world_to_screen(pos2(0.0, 0.0)), pos2(0.0, 0.0)));
+    }
+}
@@ -1,9 +1,15 @@
 use std::{
+    cell::RefCell,
     ops::{Deref, DerefMut},
+    rc::Rc,
     sync::{Arc, Mutex},
 };
 
-use crate::{GAssets, GGApp, GGRunOptions, InitContext};
+use crate::{
+    engine_rhai::ScriptHost,
+    ScriptError, timestep::FixedTimestep, GAssets, GGApp, GGAsset, GGAtlas,
+    GGRunOptions, InitContext,
+};
 use eframe::{
     egui::{self, Align2, Color32, FontId, LayerId},
     egui_glow, glow,
@@ -22,14 +28,20 @@ pub struct GGEngine {
     pub(crate) assets: ArcSendMutex<GAssets>,
     pub(crate) rhai_engine: rhai::Engine,
     pub(crate) rhai_ast: rhai::AST,
-    pub(crate) audio_manager: AudioManager,
+    pub(crate) rhai_scripts: Vec<Rc<GGAsset<rhai::AST>>>,
+    pub(crate) script_host: Rc<RefCell<ScriptHost>>,
+    pub(crate) script_errors: Vec<ScriptError>,
+    pub(crate) script_error_overlay: bool,
+    pub(crate) audio_manager: Option<AudioManager>,
     pub(crate) iterations: u64,
     pub(crate) app: ArcSendMutex<dyn GGApp>,
     pub(crate) last_update: Instant,
     pub(crate) state: GGEngineState,
+    pub(crate) headless: bool,
+    pub(crate) fixed_timestep: Option<FixedTimestep>,
 }
 
-pub struct ArcSendMutex<T: ?Sized>(Arc<Mutex<T>>);
+pub struct ArcSendMutex<T: ?Sized>(pub(crate) Arc<Mutex<T>>);
 impl<T> ArcSendMutex<T> {
     pub fn new(t: T) -> Self {
         Self(Arc::new(Mutex::new(t)))
@@ -55,18 +67,43 @@ impl<T: ?Sized> DerefMut for ArcSendMutex<T> {
 }
 
 impl GGEngine {
-    fn new<T: GGApp + 'static>(app: T) -> Self {
+    fn new<T: GGApp + 'static>(app: T, options: &GGRunOptions) -> Self {
+        Self::with_app(ArcSendMutex(Arc::new(Mutex::new(app))), options, false)
+    }
+
+    pub(crate) fn with_app(
+        app: ArcSendMutex<dyn GGApp>,
+        options: &GGRunOptions,
+        headless: bool,
+    ) -> Self {
         let rhai_engine = rhai::Engine::new();
+        let audio_manager = if headless {
+            None
+        } else {
+            Self::create_audio_manager()
+        };
         let mut engine = Self {
-            assets: ArcSendMutex::new(GAssets::default()),
+            assets: ArcSendMutex::new(
+                GAssets::default()
+                    .with_hot_reload(options.hot_reload)
+                    .with_workers(options.asset_workers)
+                    .with_sources(options.asset_sources.clone()),
+            ),
             last_update: Instant::now(),
-            app: ArcSendMutex(Arc::new(Mutex::new(app))),
+            app,
             iterations: 0,
             rhai_engine,
             rhai_ast: Default::default(),
-            audio_manager: AudioManager::new(Default::default())
-                .expect("failed to initialize audio manager"),
+            rhai_scripts: Default::default(),
+            script_host: Default::default(),
+            script_errors: Default::default(),
+            script_error_overlay: options.script_error_overlay,
+            audio_manager,
             state: GGEngineState::Preinit,
+            headless,
+            fixed_timestep: options
+                .fixed_update_rate
+                .and_then(|rate| FixedTimestep::new(rate, options.fixed_update_max_steps)),
         };
 
         engine.rhai_register_functions();
@@ -74,6 +111,24 @@ impl GGEngine {
         engine
     }
 
+    fn create_audio_manager() -> Option<AudioManager> {
+        match AudioManager::new(Default::default()) {
+            Ok(audio_manager) => Some(audio_manager),
+            Err(err) => {
+                tracing::warn!("failed to initialize audio manager: {err}");
+                None
+            }
+        }
+    }
+
+    pub fn state(&self) -> GGEngineState {
+        self.state
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn is_web() -> bool {
         false
@@ -87,7 +142,7 @@ impl GGEngine {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn run<T: GGApp + 'static>(app: T, options: GGRunOptions) {
         tracing_subscriber::fmt::init();
-        let engine = Self::new(app);
+        let engine = Self::new(app, &options);
         let size = options.window_initial_size.unwrap_or((640.0, 480.0));
         let eframe_options = eframe::NativeOptions {
             viewport: egui::ViewportBuilder::default().with_inner_size([size.0, size.1]),
@@ -140,7 +195,7 @@ impl GGEngine {
                 canvas.set_height(size.1 as u32);
             }
 
-            let engine = Self::new(game);
+            let engine = Self::new(game, &options);
 
             let web_options = eframe::WebOptions {
                 depth_buffer: options.depth_buffer,
@@ -152,15 +207,28 @@ impl GGEngine {
         });
     }
 
-    pub fn update(&mut self, egui_ctx: &egui::Context, gl: &glow::Context) {
+    pub fn update(&mut self, egui_ctx: &egui::Context, gl: &Arc<glow::Context>) {
         let now = web_time::Instant::now();
         let dt = now - self.last_update;
         self.last_update = now;
         let dt = dt.as_secs_f32();
 
-        self.assets.lock().unwrap().poll(crate::PollContext {
-            egui_ctx: &egui_ctx,
-        });
+        self.step(egui_ctx, Some(gl), dt);
+        egui_ctx.request_repaint();
+    }
+
+    /// advances the engine a single frame with the given `dt`
+    ///
+    /// `gl` is `None` when running headless, in which case `paint_glow` is never invoked
+    pub(crate) fn step(&mut self, egui_ctx: &egui::Context, gl: Option<&Arc<glow::Context>>, dt: f32) {
+        self.assets
+            .lock()
+            .unwrap()
+            .poll(crate::PollContext {
+                egui_ctx,
+                rhai_engine: &self.rhai_engine,
+                gl,
+            });
 
         match self.state {
             GGEngineState::Preinit => {
@@ -176,21 +244,20 @@ impl GGEngine {
                     Color32::WHITE,
                 );
                 egui_ctx.input(|x| {
-                    let any_input_events = x
-                        .events
-                        .iter()
-                        .filter(|x| match x {
-                            egui::Event::Key { .. } => true,
-                            egui::Event::PointerButton { .. } => true,
-                            egui::Event::Touch { .. } => true,
-                            _ => false,
-                        })
-                        .count()
-                        > 0;
-                    if any_input_events {
+                    let any_input_events = x.events.iter().any(|x| {
+                        matches!(
+                            x,
+                            egui::Event::Key { .. }
+                                | egui::Event::PointerButton { .. }
+                                | egui::Event::Touch { .. }
+                        )
+                    });
+                    if self.headless {
+                        // no audio device to unlock, so there is nothing to wait for
+                        self.state = GGEngineState::Init;
+                    } else if any_input_events {
                         // recreate audiomanager to ensure it works on the web
-                        self.audio_manager = AudioManager::new(Default::default())
-                            .expect("failed to initialize audio manager");
+                        self.audio_manager = Self::create_audio_manager();
                         self.state = GGEngineState::Init;
                     }
                 });
@@ -203,9 +270,36 @@ impl GGEngine {
                 self.state = GGEngineState::Postinit;
             }
             GGEngineState::Postinit => {
-                self.assets.lock().unwrap().poll(crate::PollContext {
-                    egui_ctx: &egui_ctx,
-                });
+                self.assets
+                    .lock()
+                    .unwrap()
+                    .poll(crate::PollContext {
+                        egui_ctx,
+                        rhai_engine: &self.rhai_engine,
+                        gl,
+                    });
+                self.rhai_sync_scripts();
+
+                let mut alpha = 1.0;
+                let fixed_dt = self.fixed_timestep.as_ref().map(|fixed_timestep| fixed_timestep.dt);
+                if let Some(fixed_timestep) = &mut self.fixed_timestep {
+                    let steps = fixed_timestep.advance(dt);
+                    alpha = fixed_timestep.alpha();
+                    for _ in 0..steps {
+                        self.app.lock().unwrap().fixed_update(crate::UpdateContext {
+                            egui_ctx,
+                            rhai_engine: &mut self.rhai_engine,
+                            rhai_ast: &self.rhai_ast,
+                            audio_manager: self.audio_manager.as_mut(),
+                            dt: fixed_timestep.dt,
+                            alpha: 0.0,
+                            fixed_dt,
+                            assets: &mut self.assets.lock().unwrap(),
+                        });
+                    }
+                }
+
+                self.rhai_run_scripts(egui_ctx, dt);
 
                 let screen_rect = egui_ctx.screen_rect();
                 let app = self.app.clone();
@@ -213,11 +307,13 @@ impl GGEngine {
                 let callback = egui::PaintCallback {
                     rect: screen_rect,
                     callback: std::sync::Arc::new(egui_glow::CallbackFn::new(
-                        move |_info, painter| {
+                        move |info, painter| {
                             app.lock().unwrap().paint_glow(crate::PaintGlowContext {
                                 dt,
+                                alpha,
                                 assets: &mut assets.lock().unwrap(),
-                                painter: painter,
+                                painter,
+                                info: &info,
                             });
                         },
                     )),
@@ -227,30 +323,42 @@ impl GGEngine {
                     egui_ctx,
                     rhai_engine: &mut self.rhai_engine,
                     rhai_ast: &self.rhai_ast,
-                    audio_manager: &mut self.audio_manager,
+                    audio_manager: self.audio_manager.as_mut(),
                     dt,
+                    alpha,
+                    fixed_dt,
                     assets: &mut self.assets.lock().unwrap(),
                 });
 
-                egui_ctx.layer_painter(LayerId::background()).add(callback);
+                if gl.is_some() {
+                    egui_ctx.layer_painter(LayerId::background()).add(callback);
+                }
 
                 self.app.lock().unwrap().update(crate::UpdateContext {
                     egui_ctx,
                     rhai_engine: &mut self.rhai_engine,
                     rhai_ast: &self.rhai_ast,
-                    audio_manager: &mut self.audio_manager,
+                    audio_manager: self.audio_manager.as_mut(),
                     dt,
+                    alpha,
+                    fixed_dt,
                     assets: &mut self.assets.lock().unwrap(),
                 });
+
+                self.rhai_show_errors(egui_ctx);
             }
         }
 
         self.iterations += 1;
-        egui_ctx.request_repaint();
     }
 
-    pub fn load_script(&self, _path: &str) {}
-    pub fn load_atlas(&self, _path: &str, _name: &str) {}
+    pub fn load_script(&mut self, path: &str) {
+        self.assets.lock().unwrap().load::<rhai::AST>(path, path);
+    }
+
+    pub fn load_atlas(&mut self, path: &str, name: &str) {
+        self.assets.lock().unwrap().load::<GGAtlas>(path, name);
+    }
 }
 
 impl eframe::App for GGEngine {
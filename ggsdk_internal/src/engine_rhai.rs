@@ -1,21 +1,537 @@
-use rhai::CallFnOptions;
-use crate::GGEngine;
+use std::{cell::RefCell, rc::Rc, sync::MutexGuard};
+
+use eframe::egui::{self, Color32, LayerId, Rect};
+use kira::sound::static_sound::StaticSoundData;
+use rhai::{CallFnOptions, EvalAltResult, FLOAT, INT};
+use crate::{ArcSendMutex, GAssets, GGAsset, GGAtlas, GGEngine, GGPainter};
+
+/// State shared between the engine and the functions registered with rhai.
+#[derive(Default)]
+pub(crate) struct ScriptHost {
+    /// the context of the current frame, only set while scripts are running
+    egui_ctx: Option<egui::Context>,
+    /// names of sounds requested by scripts, played once the scripts return
+    sounds: Vec<String>,
+    started: bool,
+    /// variables declared by the top level statements of the loaded scripts, kept between calls
+    scope: rhai::Scope<'static>,
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// An error raised while calling into a script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptError {
+    /// the function that was called by the engine or app
+    pub function: String,
+    /// path of the script the error originated from, if known
+    pub source: Option<String>,
+    pub line: Option<usize>,
+    pub position: Option<usize>,
+    pub message: String,
+}
+
+impl ScriptError {
+    fn new(function: &str, err: &EvalAltResult, scripts: &[Rc<GGAsset<rhai::AST>>]) -> Self {
+        // unwrap nested function calls to find where the error was raised
+        let mut err = err;
+        let mut source = None;
+        let mut inner_function = function;
+        while let EvalAltResult::ErrorInFunctionCall(f, src, inner, _) = err {
+            if !src.is_empty() {
+                source = Some(src.clone());
+            }
+            inner_function = f;
+            err = inner;
+        }
+
+        // functions of combined scripts may lose their source, fall back to the defining script
+        let source = source.or_else(|| {
+            scripts
+                .iter()
+                .rev()
+                .find(|s| s.iter_functions().any(|f| f.name == inner_function))
+                .map(|s| s.path.clone())
+        });
+
+        let pos = err.position();
+        Self {
+            function: function.to_string(),
+            source,
+            line: pos.line(),
+            position: pos.position(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source.as_deref().unwrap_or("<script>"))?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        if let Some(position) = self.position {
+            write!(f, ":{position}")?;
+        }
+        write!(f, ": {} (calling {})", self.message, self.function)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+fn lock_assets(assets: &ArcSendMutex<GAssets>) -> ScriptResult<MutexGuard<'_, GAssets>> {
+    // the app holds the lock while it runs, e.g. when calling into scripts from `update`
+    assets
+        .try_lock()
+        .map_err(|_| "assets are not accessible while the app is using them".into())
+}
+
+fn with_egui_ctx<R>(
+    host: &Rc<RefCell<ScriptHost>>,
+    f: impl FnOnce(&egui::Context) -> R,
+) -> ScriptResult<R> {
+    match &host.borrow().egui_ctx {
+        Some(egui_ctx) => Ok(f(egui_ctx)),
+        None => Err("input and drawing is only available from start() and update(dt)".into()),
+    }
+}
+
+fn key(name: &str) -> ScriptResult<egui::Key> {
+    egui::Key::from_name(name).ok_or_else(|| format!("unknown key: {name}").into())
+}
+
+fn register_asset_type<T: 'static + Clone>(
+    rhai_engine: &mut rhai::Engine,
+    assets: &ArcSendMutex<GAssets>,
+    type_name: &str,
+) {
+    let a = assets.clone();
+    rhai_engine.register_fn(
+        format!("load_{type_name}"),
+        move |path: &str, name: &str| -> ScriptResult<()> {
+            lock_assets(&a)?.load::<T>(path, name);
+            Ok(())
+        },
+    );
+    let a = assets.clone();
+    rhai_engine.register_fn(
+        format!("has_{type_name}"),
+        move |name: &str| -> ScriptResult<bool> { Ok(lock_assets(&a)?.get::<T>(name).is_some()) },
+    );
+}
 
 impl GGEngine {
     pub(crate) fn rhai_register_functions(&mut self) {
-       /* let scripts = self.scripts.clone();
-        self.rhai_engine.register_fn("load_script", move |x: &str| {
-            scripts.borrow_mut().load(x, x);
-        });*/
-    }
-    
-    pub fn call_function(&self, name: &str, args: impl rhai::FuncArgs) {
-        let _ = self.rhai_engine.call_fn_with_options::<()>(
+        let rhai_engine = &mut self.rhai_engine;
+        let assets = &self.assets;
+        let host = &self.script_host;
+
+        // assets
+        register_asset_type::<GGAtlas>(rhai_engine, assets, "atlas");
+        register_asset_type::<StaticSoundData>(rhai_engine, assets, "sound");
+        register_asset_type::<String>(rhai_engine, assets, "text");
+        register_asset_type::<tiled::Map>(rhai_engine, assets, "map");
+        register_asset_type::<rhai::AST>(rhai_engine, assets, "script");
+        let a = assets.clone();
+        rhai_engine.register_fn("get_text", move |name: &str| -> ScriptResult<String> {
+            match lock_assets(&a)?.get::<String>(name) {
+                Some(text) => Ok(text.data.clone()),
+                None => Err(format!("text {name} is not loaded").into()),
+            }
+        });
+        let a = assets.clone();
+        rhai_engine.register_fn("assets_pending", move || -> ScriptResult<INT> {
+            Ok(lock_assets(&a)?.pending() as INT)
+        });
+        let a = assets.clone();
+        rhai_engine.register_fn("assets_total", move || -> ScriptResult<INT> {
+            Ok(lock_assets(&a)?.total() as INT)
+        });
+
+        // audio
+        let h = host.clone();
+        rhai_engine.register_fn("play_sound", move |name: &str| {
+            h.borrow_mut().sounds.push(name.to_string());
+        });
+
+        // input
+        let h = host.clone();
+        rhai_engine.register_fn("key_down", move |name: &str| -> ScriptResult<bool> {
+            let key = key(name)?;
+            with_egui_ctx(&h, |ctx| ctx.input(|i| i.key_down(key)))
+        });
+        let h = host.clone();
+        rhai_engine.register_fn("key_pressed", move |name: &str| -> ScriptResult<bool> {
+            let key = key(name)?;
+            with_egui_ctx(&h, |ctx| ctx.input(|i| i.key_pressed(key)))
+        });
+        let h = host.clone();
+        rhai_engine.register_fn("pointer_down", move || -> ScriptResult<bool> {
+            with_egui_ctx(&h, |ctx| ctx.input(|i| i.pointer.primary_down()))
+        });
+        let h = host.clone();
+        rhai_engine.register_fn("pointer_pos", move || -> ScriptResult<rhai::Array> {
+            with_egui_ctx(&h, |ctx| match ctx.input(|i| i.pointer.latest_pos()) {
+                Some(pos) => vec![(pos.x as FLOAT).into(), (pos.y as FLOAT).into()],
+                None => rhai::Array::new(),
+            })
+        });
+
+        // drawing
+        let a = assets.clone();
+        let h = host.clone();
+        rhai_engine.register_fn(
+            "draw_atlas",
+            move |name: &str, index: INT, x: FLOAT, y: FLOAT, width: FLOAT, height: FLOAT| -> ScriptResult<()> {
+                let index = u16::try_from(index).map_err(|_| format!("atlas index {index} is out of range"))?;
+                let Some(atlas) = lock_assets(&a)?.get::<GGAtlas>(name) else {
+                    return Err(format!("atlas {name} is not loaded").into());
+                };
+                let rect = Rect::from_min_size(
+                    (x as f32, y as f32).into(),
+                    (width as f32, height as f32).into(),
+                );
+                with_egui_ctx(&h, |ctx| {
+                    ctx.layer_painter(LayerId::background())
+                        .atlas(&atlas, index, rect, Color32::WHITE)
+                })
+            },
+        );
+    }
+
+    fn rhai_has_function(&self, name: &str, params: usize) -> bool {
+        self.rhai_ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == params)
+    }
+
+    /// calls `start()` once and `update(dt)` every frame if the loaded scripts define them
+    pub(crate) fn rhai_run_scripts(&mut self, egui_ctx: &egui::Context, dt: f32) {
+        self.script_host.borrow_mut().egui_ctx = Some(egui_ctx.clone());
+
+        if !self.script_host.borrow().started && self.rhai_has_function("start", 0) {
+            self.script_host.borrow_mut().started = true;
+            self.call_function("start", ());
+        }
+        if self.rhai_has_function("update", 1) {
+            self.call_function("update", (dt as FLOAT,));
+        }
+
+        let sounds = {
+            let mut host = self.script_host.borrow_mut();
+            host.egui_ctx = None;
+            std::mem::take(&mut host.sounds)
+        };
+        let assets = self.assets.lock().unwrap();
+        for name in sounds {
+            let Some(sound) = assets.get::<StaticSoundData>(&name) else {
+                tracing::warn!("script tried to play sound {name} which is not loaded");
+                continue;
+            };
+            if let Some(audio_manager) = &mut self.audio_manager {
+                let _ = audio_manager.play(sound.data.clone());
+            }
+        }
+    }
+
+    /// merges all loaded scripts into `rhai_ast` whenever a script is loaded or reloaded
+    pub(crate) fn rhai_sync_scripts(&mut self) {
+        let scripts = self.assets.lock().unwrap().get_all::<rhai::AST>();
+
+        let unchanged = scripts.len() == self.rhai_scripts.len()
+            && scripts
+                .iter()
+                .zip(self.rhai_scripts.iter())
+                .all(|(a, b)| Rc::ptr_eq(a, b));
+        if unchanged {
+            return;
+        }
+
+        let mut ast = rhai::AST::empty();
+        for script in scripts.iter() {
+            ast.combine(script.data.clone());
+        }
+        self.rhai_ast = ast;
+        self.rhai_scripts = scripts;
+
+        // top level statements of every script run again, starting from an empty scope,
+        // and start() is called again to fill it as on the first load
+        let mut scope = rhai::Scope::new();
+        let res = self.rhai_engine.run_ast_with_scope(&mut scope, &self.rhai_ast);
+        {
+            let mut host = self.script_host.borrow_mut();
+            host.scope = scope;
+            host.started = false;
+        }
+        if let Err(err) = res {
+            let err = ScriptError::new("<top level>", &err, &self.rhai_scripts);
+            self.rhai_report_error(err);
+        }
+    }
+
+    /// calls a script function, discarding its return value
+    ///
+    /// errors are logged and shown in the error overlay, see `try_call_function`
+    pub fn call_function(&mut self, name: &str, args: impl rhai::FuncArgs) {
+        if let Err(err) = self.try_call_function::<rhai::Dynamic>(name, args) {
+            self.rhai_report_error(err);
+        }
+    }
+
+    /// calls a script function and returns its value
+    ///
+    /// the function sees the variables declared by the top level statements of the scripts
+    pub fn try_call_function<T: Clone + 'static>(
+        &self,
+        name: &str,
+        args: impl rhai::FuncArgs,
+    ) -> Result<T, ScriptError> {
+        // taken out of the host, the registered functions borrow it while the script runs
+        let mut scope = std::mem::take(&mut self.script_host.borrow_mut().scope);
+        let res = self.rhai_engine.call_fn_with_options::<T>(
             CallFnOptions::new().eval_ast(false),
-            &mut rhai::Scope::new(),
+            &mut scope,
             &self.rhai_ast,
             name,
             args,
         );
+        self.script_host.borrow_mut().scope = scope;
+        res.map_err(|err| ScriptError::new(name, &err, &self.rhai_scripts))
+    }
+
+    /// errors raised by scripts since the last call to `clear_script_errors`
+    pub fn script_errors(&self) -> &[ScriptError] {
+        &self.script_errors
+    }
+
+    pub fn clear_script_errors(&mut self) {
+        self.script_errors.clear();
+    }
+
+    fn rhai_report_error(&mut self, err: ScriptError) {
+        // scripts called every frame would otherwise flood the log with the same error
+        if self.script_errors.contains(&err) {
+            return;
+        }
+        tracing::error!("{err}");
+        self.script_errors.push(err);
+    }
+
+    /// draws the errors raised by scripts on top of the app
+    pub(crate) fn rhai_show_errors(&mut self, egui_ctx: &egui::Context) {
+        if !self.script_error_overlay || self.script_errors.is_empty() {
+            return;
+        }
+        let mut clear = false;
+        egui::Window::new("Script errors")
+            .order(egui::Order::Foreground)
+            .show(egui_ctx, |ui| {
+                for err in self.script_errors.iter() {
+                    ui.colored_label(Color32::LIGHT_RED, err.to_string());
+                }
+                clear = ui.button("Clear").clicked();
+            });
+        if clear {
+            self.clear_script_errors();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        headless::fixture::{hot_reload, touch},
+        GGApp, GGEngine, GGHeadless, InitContext, UpdateContext,
+    };
+
+    struct ScriptApp {
+        path: String,
+    }
+
+    impl GGApp for ScriptApp {
+        fn init(&mut self, g: InitContext) {
+            g.assets.load::<rhai::AST>(&self.path, "script");
+        }
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    #[test]
+    fn test_script_reload() {
+        let path = std::env::temp_dir().join(format!("ggsdk_script_{}.rhai", std::process::id()));
+        std::fs::write(&path, "fn answer() { 41 }").unwrap();
+
+        let file = path.to_string_lossy().to_string();
+        let mut headless = hot_reload(move |assets| {
+            assets.load::<rhai::AST>(&file, "script");
+        });
+        headless.step();
+
+        fn answer(engine: &GGEngine) -> i64 {
+            engine
+                .rhai_engine
+                .call_fn(&mut rhai::Scope::new(), &engine.rhai_ast, "answer", ())
+                .unwrap()
+        }
+        assert_eq!(answer(headless.engine()), 41);
+
+        std::fs::write(&path, "fn answer() { 42 }").unwrap();
+        touch(&path);
+        let reloaded = |h: &mut GGHeadless<_>| answer(h.engine()) == 42;
+        assert!(headless.step_until(reloaded, Duration::from_secs(10)), "script was not reloaded");
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_script_lifecycle() {
+        let path = std::env::temp_dir().join(format!("ggsdk_lifecycle_{}.rhai", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+                fn start() {
+                    load_text("Cargo.toml", "started");
+                }
+                fn update(dt) {
+                    if assets_total() == 2 && dt == 0.25 && !key_down("Space") && pointer_pos().is_empty() {
+                        load_text("src/lib.rs", "updated");
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+
+        let app = ScriptApp {
+            path: path.to_string_lossy().to_string(),
+        };
+        let mut headless = GGEngine::headless(app, Default::default());
+        headless.dt = 0.25;
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        headless.step();
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        assert!(headless.assets().get::<String>("started").is_some());
+        assert!(headless.assets().get::<String>("updated").is_some());
+
+        // drawing and input is only available while the engine runs the scripts
+        let engine = headless.engine();
+        let res = engine.rhai_engine.eval::<bool>(r#"key_down("A")"#);
+        assert!(res.is_err());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_script_scope() {
+        let path = std::env::temp_dir().join(format!("ggsdk_scope_{}.rhai", std::process::id()));
+        std::fs::write(
+            &path,
+            "let frames = 0;\nlet started = false;\nfn start() { started = true; }\nfn update(dt) { frames += 1; }\nfn frame_count() { frames }\n",
+        )
+        .unwrap();
+
+        let app = ScriptApp {
+            path: path.to_string_lossy().to_string(),
+        };
+        let mut headless = GGEngine::headless(app, Default::default());
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        headless.step_n(3);
+
+        let engine = headless.engine();
+        assert!(engine.script_errors().is_empty());
+        assert!(engine.script_host.borrow().scope.get_value::<bool>("started").unwrap());
+        let frames = engine.try_call_function::<i64>("frame_count", ()).unwrap();
+        headless.step();
+        assert_eq!(headless.engine().try_call_function::<i64>("frame_count", ()), Ok(frames + 1));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_script_reload_scope() {
+        let path = std::env::temp_dir().join(format!("ggsdk_reload_scope_{}.rhai", std::process::id()));
+        let script = |balls: usize| {
+            format!("let balls = [];\nfn start() {{ for i in 0..{balls} {{ balls.push(i); }} }}\nfn ball_count() {{ balls.len() }}\n")
+        };
+        std::fs::write(&path, script(1)).unwrap();
+
+        let file = path.to_string_lossy().to_string();
+        let mut headless = hot_reload(move |assets| {
+            assets.load::<rhai::AST>(&file, "script");
+        });
+        headless.step();
+        assert_eq!(headless.engine().try_call_function::<i64>("ball_count", ()), Ok(1));
+
+        // the scope is rebuilt from the top level statements and filled again by start()
+        std::fs::write(&path, script(2)).unwrap();
+        touch(&path);
+        let reloaded = |h: &mut GGHeadless<_>| h.engine().try_call_function::<i64>("ball_count", ()) == Ok(2);
+        assert!(headless.step_until(reloaded, Duration::from_secs(10)), "script state was not rebuilt");
+        assert!(headless.engine().script_errors().is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_basic_script() {
+        let app = ScriptApp {
+            path: "../examples/treasure_hunter/assets/basic.rhai".to_string(),
+        };
+        let mut headless = GGEngine::headless(app, Default::default());
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        headless.step_n(10);
+
+        let engine = headless.engine();
+        assert_eq!(engine.script_errors(), []);
+        let balls = engine.script_host.borrow().scope.get_value::<rhai::Array>("balls").unwrap();
+        assert_eq!(balls.len(), 10);
+        let x = balls[1].clone_cast::<rhai::Map>()["x"].as_float().unwrap();
+        assert!(x > 32.0, "balls should move every update");
+        // the atlas the script loads is relative to the example, so nothing is drawn here
+        let res = engine.rhai_engine.eval::<()>(r#"draw_atlas("basic", -1, 0.0, 0.0, 1.0, 1.0)"#);
+        assert!(res.unwrap_err().to_string().contains("atlas index -1 is out of range"));
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let path = std::env::temp_dir().join(format!("ggsdk_errors_{}.rhai", std::process::id()));
+        std::fs::write(
+            &path,
+            "fn answer() { 42 }\n\nfn fail() {\n    let x = 1;\n    x.missing()\n}\n\nfn update(dt) { fail() }\n",
+        )
+        .unwrap();
+        let source = path.to_string_lossy().to_string();
+
+        let app = ScriptApp {
+            path: source.clone(),
+        };
+        let mut headless = GGEngine::headless(app, Default::default());
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        headless.step();
+
+        let engine = headless.engine();
+        assert_eq!(engine.try_call_function::<i64>("answer", ()), Ok(42));
+
+        let err = engine.try_call_function::<()>("fail", ()).unwrap_err();
+        assert_eq!(err.function, "fail");
+        assert_eq!(err.source.as_deref(), Some(source.as_str()));
+        assert_eq!(err.line, Some(5));
+        assert_eq!(err.position, Some(7));
+
+        let err = engine.try_call_function::<i64>("missing", ()).unwrap_err();
+        assert_eq!(err.source, None);
+
+        // update(dt) fails every frame but is only reported once
+        headless.step_n(3);
+        let engine = headless.engine();
+        assert_eq!(engine.script_errors().len(), 1);
+        assert_eq!(engine.script_errors()[0].function, "update");
+        assert_eq!(engine.script_errors()[0].line, Some(5));
+        engine.clear_script_errors();
+        assert!(engine.script_errors().is_empty());
+
+        let _ = std::fs::remove_file(path);
     }
-}
\ No newline at end of file
+}
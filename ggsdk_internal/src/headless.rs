@@ -0,0 +1,224 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use eframe::egui::{self, Rect};
+
+use crate::{ArcSendMutex, GAssets, GGApp, GGEngine, GGEngineState, GGRunOptions};
+
+/// Drives a `GGApp` without a window, GL context or audio device.
+///
+/// Every step feeds a synthetic `egui::Context` with a fixed `dt`, which makes
+/// it suitable for automated tests and CI.
+pub struct GGHeadless<T: GGApp> {
+    engine: GGEngine,
+    app: ArcSendMutex<T>,
+    egui_ctx: egui::Context,
+    screen_rect: Rect,
+    time: f64,
+    /// the `dt` passed to the app every step
+    pub dt: f32,
+}
+
+impl GGEngine {
+    /// creates a headless runner for `app`, see `GGHeadless`
+    pub fn headless<T: GGApp + 'static>(app: T, options: GGRunOptions) -> GGHeadless<T> {
+        let app = ArcSendMutex::new(app);
+        let engine = Self::with_app(
+            ArcSendMutex(app.0.clone() as Arc<Mutex<dyn GGApp>>),
+            &options,
+            true,
+        );
+        let size = options.window_initial_size.unwrap_or((640.0, 480.0));
+        GGHeadless {
+            engine,
+            app,
+            egui_ctx: egui::Context::default(),
+            screen_rect: Rect::from_min_size(Default::default(), size.into()),
+            time: 0.0,
+            dt: 1.0 / 60.0,
+        }
+    }
+}
+
+impl<T: GGApp> GGHeadless<T> {
+    /// advances the engine a single frame
+    pub fn step(&mut self) {
+        let raw_input = egui::RawInput {
+            screen_rect: Some(self.screen_rect),
+            time: Some(self.time),
+            predicted_dt: self.dt,
+            ..Default::default()
+        };
+        let engine = &mut self.engine;
+        let dt = self.dt;
+        let _ = self.egui_ctx.run(raw_input, |egui_ctx| {
+            engine.step(egui_ctx, None, dt);
+        });
+        self.time += dt as f64;
+    }
+
+    /// advances the engine `n` frames
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// steps until `done` returns `true` after a step, or `timeout` has elapsed
+    ///
+    /// returns `true` if `done` did
+    pub fn step_until(&mut self, mut done: impl FnMut(&mut Self) -> bool, timeout: Duration) -> bool {
+        let start = web_time::Instant::now();
+        loop {
+            self.step();
+            if done(self) {
+                return true;
+            }
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    /// steps until the app has been initialized and all pending assets have been
+    /// loaded, or `timeout` has elapsed
+    ///
+    /// returns `true` if all assets were loaded
+    pub fn step_until_loaded(&mut self, timeout: Duration) -> bool {
+        self.step_until(
+            |headless| matches!(headless.state(), GGEngineState::Postinit) && headless.assets().pending() == 0,
+            timeout,
+        )
+    }
+
+    pub fn app(&self) -> MutexGuard<'_, T> {
+        self.app.lock().unwrap()
+    }
+
+    pub fn assets(&self) -> MutexGuard<'_, GAssets> {
+        self.engine.assets.lock().unwrap()
+    }
+
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    pub fn engine(&mut self) -> &mut GGEngine {
+        &mut self.engine
+    }
+
+    pub fn state(&self) -> GGEngineState {
+        self.engine.state()
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.engine.iterations()
+    }
+}
+
+/// Fixtures shared by the tests of the crate.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::time::{Duration, SystemTime};
+
+    use crate::{GAssets, GGApp, GGEngine, GGHeadless, GGRunOptions, InitContext, UpdateContext};
+
+    /// An app which does nothing.
+    pub struct EmptyApp;
+
+    impl GGApp for EmptyApp {
+        fn init(&mut self, _: InitContext) {}
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    /// An app which only loads assets in `init`.
+    pub struct LoadApp(Box<dyn FnMut(&mut GAssets)>);
+
+    impl GGApp for LoadApp {
+        fn init(&mut self, g: InitContext) {
+            (self.0)(g.assets);
+        }
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    /// runs `load` headless with hot reload enabled, stepped until everything it loads is loaded
+    pub fn hot_reload(load: impl FnMut(&mut GAssets) + 'static) -> GGHeadless<LoadApp> {
+        let options = GGRunOptions {
+            hot_reload: true,
+            ..Default::default()
+        };
+        let mut headless = GGEngine::headless(LoadApp(Box::new(load)), options);
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        headless
+    }
+
+    /// moves the modification time of the file at `path` ahead, so the change is seen even
+    /// when the file system only stores whole seconds
+    pub fn touch(path: impl AsRef<std::path::Path>) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{GGApp, GGEngine, GGEngineState, InitContext, UpdateContext};
+
+    #[derive(Default)]
+    struct TestApp {
+        init: u32,
+        updates: u32,
+        time: f32,
+        manifest: Option<String>,
+    }
+
+    impl GGApp for TestApp {
+        fn init(&mut self, g: InitContext) {
+            assert!(g.gl.is_none());
+            g.assets.load::<String>("Cargo.toml", "manifest");
+            self.init += 1;
+        }
+
+        fn update(&mut self, g: UpdateContext) {
+            assert!(g.audio_manager.is_none());
+            self.updates += 1;
+            self.time += g.dt;
+            if let Some(manifest) = g.assets.get::<String>("manifest") {
+                self.manifest = Some(manifest.data.clone());
+            }
+        }
+    }
+
+    #[test]
+    fn test_headless_steps() {
+        let mut headless = GGEngine::headless(TestApp::default(), Default::default());
+        headless.dt = 0.5;
+        assert!(matches!(headless.state(), GGEngineState::Preinit));
+
+        headless.step_n(2);
+        assert!(matches!(headless.state(), GGEngineState::Postinit));
+        assert_eq!(headless.app().init, 1);
+        assert_eq!(headless.app().updates, 0);
+
+        headless.step_n(4);
+        assert_eq!(headless.iterations(), 6);
+        assert_eq!(headless.app().updates, 4);
+        assert_eq!(headless.app().time, 2.0);
+
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        assert!(headless.assets().get::<String>("manifest").is_some());
+        headless.step();
+        assert!(headless.app().manifest.as_ref().unwrap().contains("ggsdk_internal"));
+    }
+}
@@ -18,19 +18,43 @@ pub use assets::*;
 mod app;
 pub use app::*;
 
+mod headless;
+pub use headless::*;
+
 mod runoptions;
 pub use runoptions::*;
 
 mod engine_rhai;
+pub use engine_rhai::ScriptError;
+
+mod timestep;
 
 mod atlas;
 pub use atlas::*;
 
+mod animation;
+pub use animation::*;
+
 mod painter;
 pub use painter::*;
 
+mod sprite_batch;
+pub use sprite_batch::*;
+
+mod camera;
+pub use camera::*;
+
+mod map_renderer;
+pub use map_renderer::*;
+
+mod tiled_data;
+pub use tiled_data::*;
+
 pub mod persist;
 
 pub use tracing_subscriber;
 pub use tiled;
-pub use kira;
\ No newline at end of file
+pub use kira;
+pub use rhai;
+#[doc(hidden)]
+pub use include_dir;
\ No newline at end of file
@@ -0,0 +1,465 @@
+use std::rc::Rc;
+
+use eframe::egui::{pos2, vec2, Color32, Painter, Pos2, Rect, Vec2};
+
+use crate::{GAssets, GGAsset, GGAtlas, GGCamera2D, GGPainter, GGSprite, SpriteBatch};
+
+/// A sprite of a map in screen points, see `GGMapRenderer::sprites`.
+#[derive(Clone)]
+pub struct MapSprite {
+    pub atlas: Rc<GGAsset<GGAtlas>>,
+    pub index: usize,
+    pub sprite: GGSprite,
+}
+
+/// The offset, parallax factor and color a layer inherits from its groups.
+#[derive(Debug, Clone, Copy)]
+struct LayerStyle {
+    offset: Vec2,
+    parallax: Vec2,
+    color: Color32,
+}
+
+/// Draws a `tiled::Map` with the atlases loaded along with it.
+///
+/// Draws the visible tile layers, image layers and tile objects of orthogonal, isometric,
+/// staggered and hexagonal maps. The world space of the camera is in map pixels, without
+/// a camera the map is drawn with its origin at the top left of the screen.
+pub struct GGMapRenderer<'a> {
+    map: &'a tiled::Map,
+    assets: &'a GAssets,
+    camera: GGCamera2D,
+    tint: Color32,
+    time: f32,
+}
+
+/// The rect of the cell of tile `x`, `y` in map pixels.
+///
+/// Tiles taller or wider than the cell are drawn with their bottom left corner at the
+/// bottom left of the cell.
+pub fn map_tile_rect(map: &tiled::Map, x: i32, y: i32) -> Rect {
+    let (tile_width, tile_height) = (map.tile_width as f32, map.tile_height as f32);
+    let min = match map.orientation {
+        tiled::Orientation::Orthogonal => pos2(x as f32 * tile_width, y as f32 * tile_height),
+        tiled::Orientation::Isometric => pos2(
+            (x - y) as f32 * tile_width / 2.0 + (map.height as f32 - 1.0) * tile_width / 2.0,
+            (x + y) as f32 * tile_height / 2.0,
+        ),
+        tiled::Orientation::Staggered | tiled::Orientation::Hexagonal => {
+            // a staggered map is a hexagonal map with sides of length 0
+            let side = match map.orientation {
+                tiled::Orientation::Hexagonal => map.hex_side_length.unwrap_or(0) as f32,
+                _ => 0.0,
+            };
+            let staggered = |i: i32| (i & 1 == 1) == matches!(map.stagger_index, tiled::StaggerIndex::Odd);
+            match map.stagger_axis {
+                tiled::StaggerAxis::X => {
+                    let column_width = (tile_width - side) / 2.0 + side;
+                    let shift = if staggered(x) { tile_height / 2.0 } else { 0.0 };
+                    pos2(x as f32 * column_width, y as f32 * tile_height + shift)
+                }
+                tiled::StaggerAxis::Y => {
+                    let row_height = (tile_height - side) / 2.0 + side;
+                    let shift = if staggered(y) { tile_width / 2.0 } else { 0.0 };
+                    pos2(x as f32 * tile_width + shift, y as f32 * row_height)
+                }
+            }
+        }
+    };
+    Rect::from_min_size(min, vec2(tile_width, tile_height))
+}
+
+/// The position in map pixels of an object at `x`, `y`, which on isometric maps are
+/// measured along the axes of the map.
+fn object_position(map: &tiled::Map, x: f32, y: f32) -> Pos2 {
+    match map.orientation {
+        tiled::Orientation::Isometric => {
+            let (tile_width, tile_height) = (map.tile_width as f32, map.tile_height as f32);
+            let (x, y) = (x / tile_height, y / tile_height);
+            pos2(
+                (x - y) * tile_width / 2.0 + map.height as f32 * tile_width / 2.0,
+                (x + y) * tile_height / 2.0,
+            )
+        }
+        _ => pos2(x, y),
+    }
+}
+
+impl<'a> GGMapRenderer<'a> {
+    pub fn new(map: &'a tiled::Map, assets: &'a GAssets) -> Self {
+        Self {
+            map,
+            assets,
+            camera: GGCamera2D::default(),
+            tint: Color32::WHITE,
+            time: 0.0,
+        }
+    }
+
+    /// draws the map as seen by `camera`, skipping tiles outside its viewport
+    pub fn with_camera(mut self, camera: &GGCamera2D) -> Self {
+        self.camera = camera.clone();
+        self
+    }
+
+    /// tints the whole map, on top of the tint and opacity of the layers
+    pub fn with_tint(mut self, tint: Color32) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// the time in seconds used to pick the frames of animated tiles
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// the sprites of the map in drawing order, placed in screen points
+    pub fn sprites(&self) -> Vec<MapSprite> {
+        let mut sprites = Vec::new();
+        let style = LayerStyle {
+            offset: Vec2::ZERO,
+            parallax: Vec2::splat(1.0),
+            color: self.tint,
+        };
+        for layer in self.map.layers() {
+            self.add_layer(layer, style, &mut sprites);
+        }
+        for sprite in &mut sprites {
+            sprite.sprite = self.camera.transform_sprite(&sprite.sprite);
+        }
+        sprites
+    }
+
+    /// draws the map with egui
+    pub fn draw(&self, painter: &Painter) {
+        for MapSprite { atlas, index, sprite } in self.sprites() {
+            painter.sprite(&atlas, index, &sprite);
+        }
+    }
+
+    /// adds the map to `batch`, to be drawn on the glow path
+    pub fn push_to(&self, batch: &mut SpriteBatch) {
+        for MapSprite { atlas, index, sprite } in self.sprites() {
+            batch.push_sprite(&atlas, index, &sprite);
+        }
+    }
+
+    fn add_layer(&self, layer: tiled::Layer, parent: LayerStyle, sprites: &mut Vec<MapSprite>) {
+        if !layer.visible {
+            return;
+        }
+        let mut color = parent.color.gamma_multiply(layer.opacity);
+        if let Some(tint) = layer.tint_color {
+            color = color * Color32::from_rgba_unmultiplied(tint.red, tint.green, tint.blue, tint.alpha);
+        }
+        let style = LayerStyle {
+            offset: parent.offset + vec2(layer.offset_x, layer.offset_y),
+            parallax: parent.parallax * vec2(layer.parallax_x, layer.parallax_y),
+            color,
+        };
+        // a layer with a parallax factor below 1 moves slower than the camera
+        let offset = style.offset + self.camera.position.to_vec2() * (Vec2::splat(1.0) - style.parallax);
+        match layer.layer_type() {
+            tiled::LayerType::Tiles(layer) => self.add_tiles(layer, offset, color, sprites),
+            tiled::LayerType::Objects(layer) => self.add_objects(layer, offset, color, sprites),
+            tiled::LayerType::Image(layer) => {
+                let Some(image) = &layer.image else {
+                    return;
+                };
+                let Some(atlas) = self.assets.image_atlas(image) else {
+                    return;
+                };
+                let sprite = GGSprite::at(offset.to_pos2())
+                    .with_size(vec2(image.width as f32, image.height as f32))
+                    .with_pivot(Vec2::ZERO)
+                    .with_color(color);
+                sprites.push(MapSprite { atlas, index: 0, sprite });
+            }
+            tiled::LayerType::Group(group) => {
+                for layer in group.layers() {
+                    self.add_layer(layer, style, sprites);
+                }
+            }
+        }
+    }
+
+    fn add_tiles(&self, layer: tiled::TileLayer, offset: Vec2, color: Color32, sprites: &mut Vec<MapSprite>) {
+        let tiles: Vec<_> = match &layer {
+            tiled::TileLayer::Finite(layer) => (0..layer.height() as i32)
+                .flat_map(|y| (0..layer.width() as i32).map(move |x| (x, y)))
+                .filter_map(|(x, y)| Some((x, y, layer.get_tile(x, y)?)))
+                .collect(),
+            tiled::TileLayer::Infinite(layer) => {
+                let (width, height) = (tiled::ChunkData::WIDTH as i32, tiled::ChunkData::HEIGHT as i32);
+                let mut tiles: Vec<_> = layer
+                    .chunks()
+                    .flat_map(|((chunk_x, chunk_y), chunk)| {
+                        (0..height)
+                            .flat_map(move |y| (0..width).map(move |x| (x, y)))
+                            .filter_map(move |(x, y)| {
+                                Some((chunk_x * width + x, chunk_y * height + y, chunk.get_tile(x, y)?))
+                            })
+                    })
+                    .collect();
+                tiles.sort_by_key(|(x, y, _)| (*y, *x));
+                tiles
+            }
+        };
+        let visible = self.visible_rect();
+        for (x, y, tile) in tiles {
+            let tileset = tile.get_tileset();
+            let Some((atlas, index, size)) = self.tile_frame(tileset, tile.id()) else {
+                continue;
+            };
+            let cell = map_tile_rect(self.map, x, y);
+            let tile_offset = vec2(tileset.offset_x as f32, tileset.offset_y as f32);
+            let min = pos2(cell.min.x, cell.max.y - size.y) + tile_offset + offset;
+            let rect = Rect::from_min_size(min, size);
+            if visible.is_some_and(|visible| !visible.intersects(rect)) {
+                continue;
+            }
+            let sprite = GGSprite::at(rect.center())
+                .with_size(size)
+                .with_pivot(Vec2::splat(0.5))
+                .with_color(color);
+            // tiled flips diagonally first, which is a quarter turn of the texture flipped vertically
+            let sprite = match tile.flip_d {
+                true => sprite
+                    .with_rotation(std::f32::consts::FRAC_PI_2)
+                    .with_flip(tile.flip_v, !tile.flip_h),
+                false => sprite.with_flip(tile.flip_h, tile.flip_v),
+            };
+            sprites.push(MapSprite { atlas, index, sprite });
+        }
+    }
+
+    fn add_objects(&self, layer: tiled::ObjectLayer, offset: Vec2, color: Color32, sprites: &mut Vec<MapSprite>) {
+        for object in layer.objects() {
+            if !object.visible {
+                continue;
+            }
+            let Some(tile) = object.get_tile() else {
+                continue;
+            };
+            let tileset = tile.get_tileset();
+            let Some((atlas, index, image_size)) = self.tile_frame(tileset, tile.id()) else {
+                continue;
+            };
+            let size = match object.shape {
+                tiled::ObjectShape::Rect { width, height } if width > 0.0 && height > 0.0 => vec2(width, height),
+                _ => image_size,
+            };
+            // tile objects are placed by their bottom left corner, or bottom center on isometric maps
+            let pivot = match self.map.orientation {
+                tiled::Orientation::Isometric => vec2(0.5, 1.0),
+                _ => vec2(0.0, 1.0),
+            };
+            let tile_offset = vec2(tileset.offset_x as f32, tileset.offset_y as f32);
+            let pos = object_position(self.map, object.x, object.y) + tile_offset + offset;
+            let sprite = GGSprite::at(pos)
+                .with_size(size)
+                .with_pivot(pivot)
+                .with_rotation(object.rotation.to_radians())
+                .with_flip(tile.flip_h, tile.flip_v)
+                .with_color(color);
+            sprites.push(MapSprite { atlas, index, sprite });
+        }
+    }
+
+    /// the atlas, frame and size in pixels of tile `id` of `tileset`, at the current frame of its animation
+    fn tile_frame(&self, tileset: &tiled::Tileset, id: u32) -> Option<(Rc<GGAsset<GGAtlas>>, usize, Vec2)> {
+        let id = self.animated_tile(tileset, id);
+        match &tileset.image {
+            Some(_) => {
+                let size = vec2(tileset.tile_width as f32, tileset.tile_height as f32);
+                Some((self.assets.tileset_atlas(tileset)?, id as usize, size))
+            }
+            None => {
+                let tile = tileset.get_tile(id)?;
+                let image = tile.image.as_ref()?;
+                let size = vec2(image.width as f32, image.height as f32);
+                Some((self.assets.image_atlas(image)?, 0, size))
+            }
+        }
+    }
+
+    fn animated_tile(&self, tileset: &tiled::Tileset, id: u32) -> u32 {
+        let Some(tile) = tileset.get_tile(id) else {
+            return id;
+        };
+        let Some(frames) = &tile.animation else {
+            return id;
+        };
+        let total: u64 = frames.iter().map(|frame| frame.duration as u64).sum();
+        if total == 0 {
+            return id;
+        }
+        let mut time = (self.time.max(0.0) * 1000.0) as u64 % total;
+        for frame in frames {
+            if time < frame.duration as u64 {
+                return frame.tile_id;
+            }
+            time -= frame.duration as u64;
+        }
+        id
+    }
+
+    /// the part of the map inside the viewport of the camera, if it has one
+    fn visible_rect(&self) -> Option<Rect> {
+        let viewport = self.camera.viewport;
+        (viewport.width() > 0.0 && viewport.height() > 0.0).then(|| self.camera.visible_rect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Cursor, path::Path, time::Duration};
+
+    use eframe::egui::{pos2, vec2, Color32, Rect};
+
+    use super::{map_tile_rect, GGMapRenderer};
+    use crate::{GGApp, GGCamera2D, GGEngine, InitContext, UpdateContext};
+
+    fn parse_map(attributes: &str) -> tiled::Map {
+        let tmx = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" {attributes} renderorder="right-down" width="3" height="3" infinite="0" nextlayerid="1" nextobjectid="1">
+</map>"#
+        );
+        let reader = move |_: &Path| Ok::<_, std::io::Error>(Cursor::new(tmx.clone().into_bytes()));
+        tiled::Loader::with_reader(reader).load_tmx_map("map.tmx").unwrap()
+    }
+
+    #[test]
+    fn test_map_tile_rect() {
+        let min = |map: &tiled::Map, x, y| map_tile_rect(map, x, y).min;
+
+        let orthogonal = parse_map(r#"orientation="orthogonal" tilewidth="32" tileheight="32""#);
+        assert_eq!(map_tile_rect(&orthogonal, 2, 1), Rect::from_min_size(pos2(64.0, 32.0), vec2(32.0, 32.0)));
+
+        let isometric = parse_map(r#"orientation="isometric" tilewidth="64" tileheight="32""#);
+        assert_eq!(min(&isometric, 0, 0), pos2(64.0, 0.0));
+        assert_eq!(min(&isometric, 1, 0), pos2(96.0, 16.0));
+        assert_eq!(min(&isometric, 0, 1), pos2(32.0, 16.0));
+
+        let staggered = parse_map(
+            r#"orientation="staggered" staggeraxis="y" staggerindex="odd" tilewidth="64" tileheight="32""#,
+        );
+        assert_eq!(min(&staggered, 0, 1), pos2(32.0, 16.0));
+        assert_eq!(min(&staggered, 1, 2), pos2(64.0, 32.0));
+
+        let hexagonal = parse_map(
+            r#"orientation="hexagonal" hexsidelength="16" staggeraxis="y" staggerindex="odd" tilewidth="32" tileheight="32""#,
+        );
+        assert_eq!(min(&hexagonal, 0, 1), pos2(16.0, 24.0));
+        assert_eq!(min(&hexagonal, 2, 2), pos2(64.0, 48.0));
+
+        let hexagonal = parse_map(
+            r#"orientation="hexagonal" hexsidelength="16" staggeraxis="x" staggerindex="even" tilewidth="32" tileheight="32""#,
+        );
+        assert_eq!(min(&hexagonal, 0, 0), pos2(0.0, 16.0));
+        assert_eq!(min(&hexagonal, 1, 0), pos2(24.0, 0.0));
+    }
+
+    struct MapApp {
+        dir: std::path::PathBuf,
+    }
+
+    impl GGApp for MapApp {
+        fn init(&mut self, g: InitContext) {
+            let path = self.dir.join("maps/level.tmx").to_string_lossy().to_string();
+            g.assets.load::<tiled::Map>(&path, "level");
+        }
+
+        fn update(&mut self, _: UpdateContext) {}
+    }
+
+    #[test]
+    fn test_map_renderer() {
+        let dir = std::env::temp_dir().join(format!("ggsdk_map_renderer_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("maps")).unwrap();
+        std::fs::copy("../examples/treasure_hunter/assets/basic_32x32.png", dir.join("basic.png")).unwrap();
+        std::fs::copy("../examples/treasure_hunter/assets/basic_32x32.png", dir.join("sky.png")).unwrap();
+        std::fs::write(
+            dir.join("basic.tsx"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="basic" tilewidth="8" tileheight="8" tilecount="1024" columns="32">
+ <image source="basic.png" width="256" height="256"/>
+ <tile id="3">
+  <animation>
+   <frame tileid="3" duration="100"/>
+   <frame tileid="4" duration="250"/>
+  </animation>
+ </tile>
+</tileset>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("maps/level.tmx"),
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="2" height="1" tilewidth="8" tileheight="8" infinite="0" nextlayerid="6" nextobjectid="2">
+ <tileset firstgid="1" source="../basic.tsx"/>
+ <layer id="1" name="ground" width="2" height="1" opacity="0.5" tintcolor="#ff0000">
+  <data encoding="csv">1,2147483650</data>
+ </layer>
+ <layer id="2" name="turned" width="2" height="1">
+  <data encoding="csv">536870913,0</data>
+ </layer>
+ <layer id="3" name="hidden" width="2" height="1" visible="0">
+  <data encoding="csv">1,1</data>
+ </layer>
+ <imagelayer id="4" name="sky" offsetx="10" offsety="20" parallaxx="0.5" parallaxy="0.5">
+  <image source="../sky.png" width="256" height="256"/>
+ </imagelayer>
+ <objectgroup id="5" name="things">
+  <object id="1" gid="4" x="16" y="24" width="16" height="16"/>
+  <object id="2" x="0" y="0" width="16" height="16"/>
+ </objectgroup>
+</map>"##,
+        )
+        .unwrap();
+
+        let mut headless = GGEngine::headless(MapApp { dir: dir.clone() }, Default::default());
+        assert!(headless.step_until_loaded(Duration::from_secs(10)));
+        let assets = headless.assets();
+        let map = assets.get::<tiled::Map>("level").unwrap();
+
+        let sprites = GGMapRenderer::new(&map, &assets).with_time(0.15).sprites();
+        let summary: Vec<_> = sprites.iter().map(|s| (s.index, s.sprite.pos)).collect();
+        assert_eq!(
+            summary,
+            [
+                (0, pos2(4.0, 4.0)),
+                (1, pos2(12.0, 4.0)),
+                (0, pos2(4.0, 4.0)),
+                (0, pos2(10.0, 20.0)),
+                // the animated tile shows its second frame
+                (4, pos2(16.0, 24.0)),
+            ]
+        );
+        assert_eq!(sprites[0].sprite.colors[0], Color32::WHITE.gamma_multiply(0.5) * Color32::RED);
+        assert!(!sprites[0].sprite.flip_x && sprites[1].sprite.flip_x);
+        assert_eq!(sprites[2].sprite.rotation, std::f32::consts::FRAC_PI_2);
+        assert!(!sprites[2].sprite.flip_x && sprites[2].sprite.flip_y);
+        assert!(sprites[3].atlas.name.ends_with("sky.png"));
+        assert_eq!(sprites[3].sprite.size, Some(vec2(256.0, 256.0)));
+        assert_eq!(sprites[4].sprite.pivot, Some(vec2(0.0, 1.0)));
+        assert_eq!(sprites[4].sprite.size, Some(vec2(16.0, 16.0)));
+
+        // the sky moves at half the speed of the camera
+        let viewport = Rect::from_min_size(pos2(0.0, 0.0), vec2(200.0, 100.0));
+        let camera = GGCamera2D::new(viewport).with_position(pos2(100.0, 0.0));
+        let sprites = GGMapRenderer::new(&map, &assets).with_camera(&camera).sprites();
+        assert_eq!(sprites[0].sprite.pos, pos2(4.0, 54.0));
+        assert_eq!(sprites[3].sprite.pos, pos2(60.0, 70.0));
+        // tiles outside the viewport are skipped, the image layer and objects are not
+        let camera = camera.with_position(pos2(1000.0, 0.0));
+        let sprites = GGMapRenderer::new(&map, &assets).with_camera(&camera).sprites();
+        let indices: Vec<_> = sprites.iter().map(|s| s.index).collect();
+        assert_eq!(indices, [0, 3]);
+
+        drop((map, assets));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
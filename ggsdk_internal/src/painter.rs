@@ -1,20 +1,195 @@
-use eframe::egui::{Color32, Painter, Rect};
+use eframe::egui::{emath::Rot2, epaint::Vertex, Color32, Mesh, Painter, Pos2, Rect, Shape, Vec2};
 
-use crate::GGAtlas;
+use crate::{Animator, AtlasFrame, GGAtlas};
+
+/// How to draw a sprite with `GGPainter::sprite`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GGSprite {
+    /// where the pivot of the sprite is drawn
+    pub pos: Pos2,
+    /// the size before scaling, the size of the frame in pixels when not set
+    pub size: Option<Vec2>,
+    pub scale: Vec2,
+    /// clockwise around the pivot, in radians
+    pub rotation: f32,
+    /// relative to the size, the pivot of the frame when not set
+    pub pivot: Option<Vec2>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// tints of the top left, top right, bottom right and bottom left corners
+    pub colors: [Color32; 4],
+}
+
+impl GGSprite {
+    pub fn at(pos: Pos2) -> Self {
+        Self {
+            pos,
+            size: None,
+            scale: Vec2::splat(1.0),
+            rotation: 0.0,
+            pivot: None,
+            flip_x: false,
+            flip_y: false,
+            colors: [Color32::WHITE; 4],
+        }
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: impl Into<Vec2>) -> Self {
+        self.scale = scale.into();
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self {
+        self.pivot = Some(pivot);
+        self
+    }
+
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.colors = [color; 4];
+        self
+    }
+
+    pub fn with_corner_colors(mut self, colors: [Color32; 4]) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// adds the quad of `frame` of `atlas` to `mesh`, which must use the texture of the atlas
+    pub fn add_to_mesh(&self, mesh: &mut Mesh, atlas: &GGAtlas, frame: &AtlasFrame) {
+        let size = self.size.unwrap_or(frame.rect.size()) * self.scale;
+        let pivot = self.pivot.unwrap_or(frame.pivot);
+        let rot = Rot2::from_angle(self.rotation);
+        let uv = atlas.uv(frame);
+        let (left, right) = match self.flip_x {
+            true => (uv.max.x, uv.min.x),
+            false => (uv.min.x, uv.max.x),
+        };
+        let (top, bottom) = match self.flip_y {
+            true => (uv.max.y, uv.min.y),
+            false => (uv.min.y, uv.max.y),
+        };
+        let corners = [
+            (Vec2::new(0.0, 0.0), Pos2::new(left, top)),
+            (Vec2::new(1.0, 0.0), Pos2::new(right, top)),
+            (Vec2::new(1.0, 1.0), Pos2::new(right, bottom)),
+            (Vec2::new(0.0, 1.0), Pos2::new(left, bottom)),
+        ];
+        let first = mesh.vertices.len() as u32;
+        for ((corner, uv), color) in corners.into_iter().zip(self.colors) {
+            let offset = (corner - pivot) * size;
+            mesh.vertices.push(Vertex {
+                pos: self.pos + rot * offset,
+                uv,
+                color,
+            });
+        }
+        mesh.add_triangle(first, first + 1, first + 2);
+        mesh.add_triangle(first, first + 2, first + 3);
+    }
+}
 
 pub trait GGPainter {
     fn atlas(&self, atlas: &GGAtlas, index: u16, rect: Rect, color: Color32);
+    /// draws the frame with the given name into `rect`, or nothing if there is no such frame
+    fn atlas_named(&self, atlas: &GGAtlas, name: &str, rect: Rect, color: Color32);
+    /// draws the frame with the given name at its size times `scale`, with its pivot at `pos`
+    fn atlas_frame(&self, atlas: &GGAtlas, name: &str, pos: Pos2, scale: f32, color: Color32);
+    /// draws the current frame of `animator` into `rect`
+    fn animation(&self, atlas: &GGAtlas, animator: &Animator, rect: Rect, color: Color32);
+    /// draws a frame of `atlas` moved, scaled, rotated, flipped and tinted as given by `sprite`
+    fn sprite(&self, atlas: &GGAtlas, index: usize, sprite: &GGSprite);
 }
 
 impl GGPainter for Painter {
     fn atlas(&self, atlas: &GGAtlas, index: u16, rect: Rect, color: Color32) {
-        let sw: f32 = 1.0 / atlas.cols as f32;
-        let sh = 1.0 / atlas.rows as f32;
-        let col = index % atlas.cols as u16;
-        let row = index / atlas.cols as u16;
-        let sx = col as f32 / atlas.cols as f32;
-        let sy = row as f32 / atlas.rows as f32;
-        let src = Rect::from_min_max((sx, sy).into(), (sx + sw, sy + sh).into());
-        self.image(atlas.texture.id(), rect, src, color);
+        let Some(frame) = atlas.frame(index as usize) else {
+            return;
+        };
+        self.image(atlas.texture.id(), rect, atlas.uv(frame), color);
+    }
+
+    fn atlas_named(&self, atlas: &GGAtlas, name: &str, rect: Rect, color: Color32) {
+        let Some(frame) = atlas.named_frame(name) else {
+            return;
+        };
+        self.image(atlas.texture.id(), rect, atlas.uv(frame), color);
+    }
+
+    fn atlas_frame(&self, atlas: &GGAtlas, name: &str, pos: Pos2, scale: f32, color: Color32) {
+        let Some(frame) = atlas.named_frame(name) else {
+            return;
+        };
+        let size = frame.rect.size() * scale;
+        let rect = Rect::from_min_size(pos - size * frame.pivot, size);
+        self.image(atlas.texture.id(), rect, atlas.uv(frame), color);
+    }
+
+    fn animation(&self, atlas: &GGAtlas, animator: &Animator, rect: Rect, color: Color32) {
+        let Some(frame) = animator.frame().and_then(|index| atlas.frame(index)) else {
+            return;
+        };
+        self.image(atlas.texture.id(), rect, atlas.uv(frame), color);
+    }
+
+    fn sprite(&self, atlas: &GGAtlas, index: usize, sprite: &GGSprite) {
+        let Some(frame) = atlas.frame(index) else {
+            return;
+        };
+        let mut mesh = Mesh::with_texture(atlas.texture.id());
+        sprite.add_to_mesh(&mut mesh, atlas, frame);
+        self.add(Shape::mesh(mesh));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eframe::egui::{pos2, vec2, Color32, ColorImage, Context, Mesh, Pos2};
+
+    use super::GGSprite;
+    use crate::{AtlasGrid, GGAtlas};
+
+    #[test]
+    fn test_sprite_mesh() {
+        let image = ColorImage::new([32, 16], Color32::WHITE);
+        let atlas = GGAtlas::from_grid(&Context::default(), "sprites", image, AtlasGrid::new(2, 1));
+        let frame = atlas.frame(1).unwrap();
+        let close = |a: Pos2, b: Pos2| (a - b).length() < 1e-4;
+
+        let mut mesh = Mesh::with_texture(atlas.texture_id());
+        GGSprite::at(pos2(100.0, 100.0)).add_to_mesh(&mut mesh, &atlas, frame);
+        let positions: Vec<_> = mesh.vertices.iter().map(|v| v.pos).collect();
+        assert_eq!(positions, [pos2(92.0, 92.0), pos2(108.0, 92.0), pos2(108.0, 108.0), pos2(92.0, 108.0)]);
+        assert_eq!(mesh.vertices[0].uv, pos2(0.5, 0.0));
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+
+        let sprite = GGSprite::at(pos2(0.0, 0.0))
+            .with_scale(vec2(2.0, 1.0))
+            .with_pivot(vec2(0.0, 0.0))
+            .with_rotation(std::f32::consts::FRAC_PI_2)
+            .with_flip(true, false)
+            .with_corner_colors([Color32::RED, Color32::GREEN, Color32::BLUE, Color32::BLACK]);
+        let mut mesh = Mesh::with_texture(atlas.texture_id());
+        sprite.add_to_mesh(&mut mesh, &atlas, frame);
+        // the top right corner, 32 pixels right of the pivot, is rotated below it
+        assert!(close(mesh.vertices[1].pos, pos2(0.0, 32.0)));
+        assert!(close(mesh.vertices[3].pos, pos2(-16.0, 0.0)));
+        assert_eq!(mesh.vertices[0].uv, pos2(1.0, 0.0));
+        assert_eq!(mesh.vertices[2].color, Color32::BLUE);
     }
 }
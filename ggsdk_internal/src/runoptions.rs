@@ -1,10 +1,26 @@
+use crate::AssetSource;
+
 
 pub struct GGRunOptions {
     pub window_title: String,
     pub window_initial_pos:Option<(f32, f32)>,
     pub window_initial_size:Option<(f32, f32)>,
     pub window_initial_active:Option<bool>,
-    pub depth_buffer:u8
+    pub depth_buffer:u8,
+    /// ticks per second of `GGApp::fixed_update`, `None` disables it
+    ///
+    /// a rate that is not positive and finite disables it as well
+    pub fixed_update_rate:Option<f32>,
+    /// upper bound of `GGApp::fixed_update` calls per frame
+    pub fixed_update_max_steps:u32,
+    /// show errors raised by scripts in a window on top of the app
+    pub script_error_overlay:bool,
+    /// reload assets when their files change on disk, native only
+    pub hot_reload:bool,
+    /// files read and decoded in parallel per asset type, native only
+    pub asset_workers:usize,
+    /// where assets are read from, each source is tried in order
+    pub asset_sources:Vec<AssetSource>
 }
 
 impl Default for GGRunOptions {
@@ -14,7 +30,13 @@ impl Default for GGRunOptions {
             window_initial_pos:None,
             window_initial_active:None,
             window_initial_size: None,
-            depth_buffer:1
+            depth_buffer:1,
+            fixed_update_rate:Some(60.0),
+            fixed_update_max_steps:8,
+            script_error_overlay:cfg!(debug_assertions),
+            hot_reload:cfg!(debug_assertions),
+            asset_workers:4,
+            asset_sources:vec![AssetSource::Files]
         }
     }
 }
@@ -0,0 +1,294 @@
+use std::sync::Arc;
+
+use eframe::{
+    egui::{epaint::Vertex, Color32, Mesh, PaintCallbackInfo, Rect, TextureId, Vec2},
+    egui_glow,
+    glow::{self, HasContext as _},
+};
+
+use crate::{GGAtlas, GGShader, GGSprite};
+
+// egui textures are srgb, so samples are linear and have to be converted back like egui does
+const SHADER: &str = r#"
+#shader vertex
+uniform vec2 u_screen_size;
+uniform mat3 u_view;
+in vec2 a_pos;
+in vec2 a_tc;
+in vec4 a_srgba;
+out vec4 v_rgba_gamma;
+out vec2 v_tc;
+void main() {
+    vec2 pos = (u_view * vec3(a_pos, 1.0)).xy;
+    gl_Position = vec4(2.0 * pos.x / u_screen_size.x - 1.0, 1.0 - 2.0 * pos.y / u_screen_size.y, 0.0, 1.0);
+    v_rgba_gamma = a_srgba / 255.0;
+    v_tc = a_tc;
+}
+
+#shader fragment
+uniform sampler2D u_sampler;
+in vec4 v_rgba_gamma;
+in vec2 v_tc;
+out vec4 out_color;
+vec3 srgb_gamma_from_linear(vec3 rgb) {
+    bvec3 cutoff = lessThan(rgb, vec3(0.0031308));
+    vec3 lower = rgb * vec3(12.92);
+    vec3 higher = vec3(1.055) * pow(rgb, vec3(1.0 / 2.4)) - vec3(0.055);
+    return mix(higher, lower, vec3(cutoff));
+}
+void main() {
+    vec4 texture_rgba = texture(u_sampler, v_tc);
+    vec4 texture_gamma = vec4(srgb_gamma_from_linear(texture_rgba.rgb), texture_rgba.a);
+    out_color = v_rgba_gamma * texture_gamma;
+}
+"#;
+
+/// A run of indices drawn with one texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Draw {
+    texture: TextureId,
+    start: usize,
+    end: usize,
+}
+
+/// The sprites of a `SpriteBatch`, kept apart from the gl objects.
+#[derive(Debug, Default)]
+struct Batch {
+    /// the vertices and indices of all sprites, the texture is unused
+    mesh: Mesh,
+    draws: Vec<Draw>,
+    /// set when the mesh changed since it was last uploaded
+    dirty: bool,
+}
+
+impl Batch {
+    fn push(&mut self, atlas: &GGAtlas, index: usize, sprite: &GGSprite) {
+        let Some(frame) = atlas.frame(index) else {
+            return;
+        };
+        let texture = atlas.texture_id();
+        let start = self.mesh.indices.len();
+        sprite.add_to_mesh(&mut self.mesh, atlas, frame);
+        let end = self.mesh.indices.len();
+        match self.draws.last_mut() {
+            Some(draw) if draw.texture == texture => draw.end = end,
+            _ => self.draws.push(Draw { texture, start, end }),
+        }
+        self.dirty = true;
+    }
+
+    fn len(&self) -> usize {
+        self.mesh.vertices.len() / 4
+    }
+
+    fn clear(&mut self) {
+        self.mesh.clear();
+        self.draws.clear();
+        self.dirty = true;
+    }
+}
+
+/// Draws many `GGAtlas` sprites with a few draw calls, from `GGApp::paint_glow`.
+///
+/// Sprites are pushed in points, like egui shapes, and drawn in the order they were
+/// pushed. Consecutive sprites of the same atlas share a draw call.
+///
+/// The sprites stay in the batch until `clear` is called, so a batch that does not
+/// change, like the layers of a tile map, is uploaded once and can be drawn every frame.
+///
+/// The gl objects are freed when the batch is dropped.
+pub struct SpriteBatch {
+    gl: Arc<glow::Context>,
+    shader: GGShader,
+    vertex_array: glow::VertexArray,
+    vertex_buffer: glow::Buffer,
+    index_buffer: glow::Buffer,
+    batch: Batch,
+}
+
+impl SpriteBatch {
+    /// compiles the shader and creates the buffers, use `InitContext::gl`
+    pub fn new(gl: &Arc<glow::Context>) -> Result<Self, String> {
+        let shader = GGShader::from_source(Some(gl), SHADER).map_err(|err| err.to_string())?;
+        let program = shader.program().expect("a shader compiled with gl has a program");
+        unsafe {
+            let vertex_array = gl.create_vertex_array()?;
+            let vertex_buffer = gl.create_buffer()?;
+            let index_buffer = gl.create_buffer()?;
+            gl.bind_vertex_array(Some(vertex_array));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vertex_buffer));
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(index_buffer));
+            let stride = size_of::<Vertex>() as i32;
+            let attributes = [
+                ("a_pos", 2, glow::FLOAT, std::mem::offset_of!(Vertex, pos)),
+                ("a_tc", 2, glow::FLOAT, std::mem::offset_of!(Vertex, uv)),
+                ("a_srgba", 4, glow::UNSIGNED_BYTE, std::mem::offset_of!(Vertex, color)),
+            ];
+            for (name, size, data_type, offset) in attributes {
+                let Some(location) = gl.get_attrib_location(program, name) else {
+                    continue;
+                };
+                gl.enable_vertex_attrib_array(location);
+                gl.vertex_attrib_pointer_f32(location, size, data_type, false, stride, offset as i32);
+            }
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
+
+            Ok(Self {
+                gl: gl.clone(),
+                shader,
+                vertex_array,
+                vertex_buffer,
+                index_buffer,
+                batch: Batch::default(),
+            })
+        }
+    }
+
+    /// adds frame `index` of `atlas` stretched over `rect`
+    pub fn push(&mut self, atlas: &GGAtlas, index: usize, rect: Rect, color: Color32) {
+        let sprite = GGSprite::at(rect.min)
+            .with_size(rect.size())
+            .with_pivot(Vec2::ZERO)
+            .with_color(color);
+        self.batch.push(atlas, index, &sprite);
+    }
+
+    /// adds frame `index` of `atlas` moved, scaled, rotated, flipped and tinted as given by `sprite`
+    pub fn push_sprite(&mut self, atlas: &GGAtlas, index: usize, sprite: &GGSprite) {
+        self.batch.push(atlas, index, sprite);
+    }
+
+    /// the number of sprites in the batch
+    pub fn len(&self) -> usize {
+        self.batch.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batch.len() == 0
+    }
+
+    /// the number of draw calls `draw` makes
+    pub fn draw_calls(&self) -> usize {
+        self.batch.draws.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.batch.clear();
+    }
+
+    /// draws the sprites, use `PaintGlowContext::painter` and `PaintGlowContext::info`
+    pub fn draw(&mut self, painter: &egui_glow::Painter, info: &PaintCallbackInfo) {
+        self.draw_with_view(painter, info, glam::Affine2::IDENTITY);
+    }
+
+    /// draws the sprites with their positions transformed by `view`, from points to screen points
+    pub fn draw_with_view(&mut self, painter: &egui_glow::Painter, info: &PaintCallbackInfo, view: glam::Affine2) {
+        if self.batch.draws.is_empty() {
+            return;
+        }
+        let gl = painter.gl();
+        // egui_glow sets the gl viewport to the rect of the paint callback
+        let screen_size = info.viewport.size();
+        let offset = glam::Affine2::from_translation(glam::vec2(-info.viewport.min.x, -info.viewport.min.y));
+        let view = glam::Mat3::from(offset * view);
+        self.shader.bind();
+        self.shader.set_vec2("u_screen_size", glam::vec2(screen_size.x, screen_size.y));
+        self.shader.set_mat3("u_view", &view);
+        self.shader.set_i32("u_sampler", 0);
+        unsafe {
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_vertex_array(Some(self.vertex_array));
+            if self.batch.dirty {
+                let vertices = &self.batch.mesh.vertices;
+                let indices = &self.batch.mesh.indices;
+                gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vertex_buffer));
+                gl.buffer_data_u8_slice(
+                    glow::ARRAY_BUFFER,
+                    std::slice::from_raw_parts(vertices.as_ptr().cast(), size_of_val(vertices.as_slice())),
+                    glow::DYNAMIC_DRAW,
+                );
+                gl.buffer_data_u8_slice(
+                    glow::ELEMENT_ARRAY_BUFFER,
+                    std::slice::from_raw_parts(indices.as_ptr().cast(), size_of_val(indices.as_slice())),
+                    glow::DYNAMIC_DRAW,
+                );
+                self.batch.dirty = false;
+            }
+            // blending is left as egui set it up, for premultiplied alpha
+            for draw in &self.batch.draws {
+                let Some(texture) = painter.texture(draw.texture) else {
+                    continue;
+                };
+                gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                gl.draw_elements(
+                    glow::TRIANGLES,
+                    (draw.end - draw.start) as i32,
+                    glow::UNSIGNED_INT,
+                    (draw.start * size_of::<u32>()) as i32,
+                );
+            }
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+        }
+    }
+}
+
+impl Drop for SpriteBatch {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_vertex_array(self.vertex_array);
+            self.gl.delete_buffer(self.vertex_buffer);
+            self.gl.delete_buffer(self.index_buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eframe::egui::{pos2, Color32, ColorImage, Context};
+
+    use super::{Batch, Draw, SHADER};
+    use crate::{AtlasGrid, GGAtlas, GGShader, GGSprite};
+
+    #[test]
+    fn test_sprite_batch_shader() {
+        // parsed like any shader file, without a gl context
+        let shader = GGShader::from_source(None, SHADER).unwrap();
+        assert!(shader.vertex_source.contains("u_screen_size"));
+        assert!(shader.fragment_source.contains("srgb_gamma_from_linear"));
+    }
+
+    #[test]
+    fn test_sprite_batch() {
+        let ctx = Context::default();
+        let tiles = GGAtlas::from_grid(&ctx, "tiles", ColorImage::new([32, 16], Color32::WHITE), AtlasGrid::new(2, 1));
+        let things = GGAtlas::from_grid(&ctx, "things", ColorImage::new([16, 16], Color32::WHITE), AtlasGrid::new(1, 1));
+
+        let mut batch = Batch::default();
+        for x in 0..100 {
+            batch.push(&tiles, x % 2, &GGSprite::at(pos2(x as f32 * 16.0, 0.0)));
+        }
+        batch.push(&things, 0, &GGSprite::at(pos2(0.0, 0.0)));
+        batch.push(&things, 0, &GGSprite::at(pos2(16.0, 0.0)));
+        batch.push(&tiles, 0, &GGSprite::at(pos2(32.0, 0.0)));
+        // frames that do not exist are skipped
+        batch.push(&things, 1, &GGSprite::at(pos2(0.0, 0.0)));
+
+        assert_eq!(batch.len(), 103);
+        assert!(batch.dirty);
+        let draws = [
+            Draw { texture: tiles.texture_id(), start: 0, end: 600 },
+            Draw { texture: things.texture_id(), start: 600, end: 612 },
+            Draw { texture: tiles.texture_id(), start: 612, end: 618 },
+        ];
+        assert_eq!(batch.draws, draws);
+        // the indices of a later sprite point at its own vertices
+        assert_eq!(batch.mesh.indices[606..612], [404, 405, 406, 404, 406, 407]);
+
+        batch.clear();
+        assert_eq!(batch.len(), 0);
+        assert!(batch.draws.is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+use std::fmt::Display;
+
+use eframe::egui::{pos2, vec2, Pos2, Vec2};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Why tiled properties could not be converted to a type, see `from_properties`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertiesError {
+    /// what the properties belong to, e.g. `object 3 "chest"`, empty when unknown
+    pub context: String,
+    pub message: String,
+}
+
+impl Display for PropertiesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.context.is_empty() {
+            true => write!(f, "{}", self.message),
+            false => write!(f, "{}: {}", self.context, self.message),
+        }
+    }
+}
+
+impl std::error::Error for PropertiesError {}
+
+impl PropertiesError {
+    fn new(message: impl Display) -> Self {
+        Self {
+            context: String::new(),
+            message: message.to_string(),
+        }
+    }
+
+    /// the error for a tile, layer or object without a class
+    pub fn no_class() -> Self {
+        Self::new("no class")
+    }
+
+    /// prefixes the context with what the properties belong to, e.g. `tile 3`
+    pub fn with_context(mut self, context: impl Display) -> Self {
+        self.context = match self.context.is_empty() {
+            true => context.to_string(),
+            false => format!("{context}, {}", self.context),
+        };
+        self
+    }
+}
+
+fn property_to_json(value: &tiled::PropertyValue) -> Value {
+    match value {
+        tiled::PropertyValue::BoolValue(value) => Value::from(*value),
+        tiled::PropertyValue::FloatValue(value) => Value::from(*value as f64),
+        tiled::PropertyValue::IntValue(value) => Value::from(*value),
+        // the format tiled writes colors in
+        tiled::PropertyValue::ColorValue(color) => Value::from(format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.alpha, color.red, color.green, color.blue
+        )),
+        tiled::PropertyValue::StringValue(value) | tiled::PropertyValue::FileValue(value) => Value::from(value.clone()),
+        tiled::PropertyValue::ObjectValue(id) => Value::from(*id),
+        tiled::PropertyValue::ClassValue { properties, .. } => properties_to_json(properties),
+    }
+}
+
+fn properties_to_json(properties: &tiled::Properties) -> Value {
+    Value::Object(
+        properties
+            .iter()
+            .map(|(name, value)| (name.clone(), property_to_json(value)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+/// converts custom properties to `T`, matching property names to field names
+///
+/// class properties become nested structs, colors become `#aarrggbb` strings and
+/// object references become object ids.
+pub fn from_properties<T: DeserializeOwned>(properties: &tiled::Properties) -> Result<T, PropertiesError> {
+    serde_json::from_value(properties_to_json(properties)).map_err(PropertiesError::new)
+}
+
+/// converts a class and its custom properties to the enum `T`, matching the class name
+/// to a variant and the properties to the fields of that variant
+///
+/// use `#[serde(rename_all = "lowercase")]` or `#[serde(rename = "..")]` to match the class names.
+pub fn from_class<T: DeserializeOwned>(class: Option<&str>, properties: &tiled::Properties) -> Result<T, PropertiesError> {
+    let class = class.filter(|class| !class.is_empty()).ok_or_else(PropertiesError::no_class)?;
+    let with_fields = Value::Object(Map::from_iter([(class.to_string(), properties_to_json(properties))]));
+    serde_json::from_value(with_fields)
+        .or_else(|err| match properties.is_empty() {
+            // unit variants have no fields to match
+            true => serde_json::from_value(Value::from(class)).map_err(|_| err),
+            false => Err(err),
+        })
+        .map_err(|err| PropertiesError::new(err).with_context(format!("class \"{class}\"")))
+}
+
+/// converts the class and custom properties of a tile to the enum `T`, see `from_class`
+pub fn tile_class<T: DeserializeOwned>(tile: &tiled::TileData) -> Result<T, PropertiesError> {
+    from_class(tile.user_type.as_deref(), &tile.properties)
+}
+
+/// converts the class and custom properties of a layer to the enum `T`, see `from_class`
+pub fn layer_class<T: DeserializeOwned>(layer: &tiled::LayerData) -> Result<T, PropertiesError> {
+    from_class(layer.user_type.as_deref(), &layer.properties)
+        .map_err(|err| err.with_context(format!("layer \"{}\"", layer.name)))
+}
+
+/// An object of an object layer with its class converted to `T`, see `map_objects`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapObject<T> {
+    pub id: u32,
+    pub name: String,
+    /// in map pixels
+    pub pos: Pos2,
+    /// in map pixels, zero for points
+    pub size: Vec2,
+    /// clockwise, in degrees
+    pub rotation: f32,
+    /// the tile id of a tile object, in the tileset of the tile
+    pub tile: Option<u32>,
+    pub data: T,
+}
+
+/// converts the objects of `layer` to `T` by their class, see `from_class`
+///
+/// tile objects without a class of their own use the class of their tile, and
+/// inherit the properties of the tile they do not override.
+pub fn map_objects<T: DeserializeOwned>(layer: &tiled::ObjectLayer) -> Result<Vec<MapObject<T>>, PropertiesError> {
+    layer
+        .objects()
+        .map(|object| {
+            let tile = object.get_tile();
+            let tile_data = tile.as_ref().and_then(|tile| tile.get_tile());
+            let mut properties = tile_data.as_ref().map(|tile| tile.properties.clone()).unwrap_or_default();
+            properties.extend(object.properties.clone());
+            let class = match object.user_type.is_empty() {
+                true => tile_data.as_ref().and_then(|tile| tile.user_type.clone()),
+                false => Some(object.user_type.clone()),
+            };
+            let data = from_class(class.as_deref(), &properties)
+                .map_err(|err| err.with_context(format!("object {} \"{}\"", object.id(), object.name)))?;
+            let size = match object.shape {
+                tiled::ObjectShape::Rect { width, height } | tiled::ObjectShape::Ellipse { width, height } => {
+                    vec2(width, height)
+                }
+                _ => Vec2::ZERO,
+            };
+            Ok(MapObject {
+                id: object.id(),
+                name: object.name.clone(),
+                pos: pos2(object.x, object.y),
+                size,
+                rotation: object.rotation,
+                tile: tile.map(|tile| tile.id()),
+                data,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Cursor, path::Path};
+
+    use eframe::egui::{pos2, vec2};
+    use serde::Deserialize;
+
+    use super::{from_class, from_properties, layer_class, map_objects, tile_class, PropertiesError};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Thing {
+        Player,
+        Chest { gold: u32, locked: bool },
+        Monster {
+            #[serde(default)]
+            think: u32,
+        },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Level {
+        next_level: String,
+        #[serde(default)]
+        intro: String,
+        tint: String,
+        spawn: Spawn,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Spawn {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum LayerClass {
+        Floor,
+        Things,
+    }
+
+    const MAP: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="2" height="1" tilewidth="8" tileheight="8" infinite="0" nextlayerid="3" nextobjectid="5">
+ <properties>
+  <property name="next_level" value="lvl02"/>
+  <property name="tint" type="color" value="#80ff0000"/>
+  <property name="spawn" type="class" propertytype="spawn">
+   <properties>
+    <property name="x" type="float" value="1.5"/>
+    <property name="y" type="int" value="2"/>
+   </properties>
+  </property>
+ </properties>
+ <tileset firstgid="1" name="things" tilewidth="8" tileheight="8" tilecount="2" columns="2">
+  <tile id="0" type="monster"/>
+  <tile id="1" type="chest">
+   <properties>
+    <property name="gold" type="int" value="10"/>
+    <property name="locked" type="bool" value="true"/>
+   </properties>
+  </tile>
+ </tileset>
+ <layer id="1" name="ground" class="floor" width="2" height="1">
+  <data encoding="csv">1,2</data>
+ </layer>
+ <objectgroup id="2" name="things" class="things">
+  <object id="1" name="hero" type="player" x="8" y="16"/>
+  <object id="2" name="loot" gid="2" x="16" y="24" width="8" height="8">
+   <properties>
+    <property name="gold" type="int" value="99"/>
+   </properties>
+  </object>
+  <object id="3" gid="1" x="0" y="8" width="8" height="8"/>
+ </objectgroup>
+</map>"##;
+
+    fn parse_map(tmx: &str) -> tiled::Map {
+        let tmx = tmx.to_string();
+        let reader = move |_: &Path| Ok::<_, std::io::Error>(Cursor::new(tmx.clone().into_bytes()));
+        tiled::Loader::with_reader(reader).load_tmx_map("map.tmx").unwrap()
+    }
+
+    #[test]
+    fn test_from_properties() {
+        let map = parse_map(MAP);
+        let level: Level = from_properties(&map.properties).unwrap();
+        assert_eq!(
+            level,
+            Level {
+                next_level: "lvl02".to_string(),
+                intro: String::new(),
+                tint: "#80ff0000".to_string(),
+                spawn: Spawn { x: 1.5, y: 2.0 },
+            }
+        );
+
+        let tileset = &map.tilesets()[0];
+        assert_eq!(tile_class::<Thing>(&tileset.get_tile(0).unwrap()), Ok(Thing::Monster { think: 0 }));
+        assert_eq!(
+            tile_class::<Thing>(&tileset.get_tile(1).unwrap()),
+            Ok(Thing::Chest { gold: 10, locked: true })
+        );
+        let classes: Vec<LayerClass> = map.layers().map(|layer| layer_class(&layer).unwrap()).collect();
+        assert_eq!(classes, [LayerClass::Floor, LayerClass::Things]);
+
+        let layer = map.layers().nth(1).unwrap().as_object_layer().unwrap();
+        let objects = map_objects::<Thing>(&layer).unwrap();
+        assert_eq!(objects[0].data, Thing::Player);
+        assert_eq!((objects[0].id, objects[0].name.as_str()), (1, "hero"));
+        assert_eq!(objects[0].pos, pos2(8.0, 16.0));
+        // the object overrides a property of its tile and inherits the rest
+        assert_eq!(objects[1].data, Thing::Chest { gold: 99, locked: true });
+        assert_eq!((objects[1].tile, objects[1].size), (Some(1), vec2(8.0, 8.0)));
+        assert_eq!(objects[2].data, Thing::Monster { think: 0 });
+    }
+
+    #[test]
+    fn test_properties_errors() {
+        let map = parse_map(
+            &MAP.replace(r#"type="player""#, r#"type="dragon""#)
+                .replace(r#"<property name="next_level" value="lvl02"/>"#, ""),
+        );
+        let err = from_properties::<Level>(&map.properties).unwrap_err();
+        assert_eq!(err.to_string(), "missing field `next_level`");
+
+        let layer = map.layers().nth(1).unwrap().as_object_layer().unwrap();
+        let err = map_objects::<Thing>(&layer).unwrap_err();
+        assert_eq!(err.context, r#"object 1 "hero", class "dragon""#);
+        assert!(err.message.starts_with("unknown variant `dragon`, expected one of `player`"));
+
+        let err = layer_class::<Thing>(&map.layers().next().unwrap()).unwrap_err();
+        assert_eq!(err.context, r#"layer "ground", class "floor""#);
+
+        let err = from_class::<Thing>(None, &map.properties).unwrap_err();
+        assert_eq!(err, PropertiesError::no_class());
+        assert_eq!(err.with_context("tile 3").to_string(), "tile 3: no class");
+    }
+}
@@ -0,0 +1,69 @@
+/// Accumulates frame time and hands it out in fixed sized ticks.
+pub(crate) struct FixedTimestep {
+    pub dt: f32,
+    pub max_steps: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    /// returns `None` and warns when `rate` is not a positive, finite number of ticks per second
+    pub fn new(rate: f32, max_steps: u32) -> Option<Self> {
+        if !rate.is_finite() || rate <= 0.0 {
+            tracing::warn!("invalid fixed update rate {rate}, fixed updates are disabled");
+            return None;
+        }
+        Some(Self {
+            dt: 1.0 / rate,
+            max_steps,
+            accumulator: 0.0,
+        })
+    }
+
+    /// adds `dt` to the accumulator and returns the number of fixed ticks to run
+    ///
+    /// at most `max_steps` ticks are returned, any time beyond that is dropped
+    /// to avoid a spiral of death when ticks take longer than they simulate
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt;
+        let mut steps = 0;
+        while self.accumulator >= self.dt && steps < self.max_steps {
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+        if self.accumulator >= self.dt {
+            tracing::debug!("fixed timestep fell behind, dropping {}s", self.accumulator - self.accumulator % self.dt);
+            self.accumulator %= self.dt;
+        }
+        steps
+    }
+
+    /// how far the accumulator is into the next tick, between 0.0 and 1.0
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.dt
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FixedTimestep;
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut timestep = FixedTimestep::new(4.0, 8).unwrap();
+        assert_eq!(timestep.advance(0.125), 0);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(0.125), 1);
+        assert_eq!(timestep.alpha(), 0.0);
+        assert_eq!(timestep.advance(0.5), 2);
+
+        // spiral of death, excess time is dropped
+        assert_eq!(timestep.advance(10.125), 8);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(0.125), 1);
+
+        assert!(FixedTimestep::new(0.0, 8).is_none());
+        assert!(FixedTimestep::new(-60.0, 8).is_none());
+        assert!(FixedTimestep::new(f32::INFINITY, 8).is_none());
+        assert!(FixedTimestep::new(f32::NAN, 8).is_none());
+    }
+}